@@ -53,8 +53,8 @@ class EQParameterMapper:
         - low_mid_pct (250-500 Hz)
         - mid_pct (500-2k Hz)
         - upper_mid_pct (2k-4k Hz)
-        - presence_pct (4k-6k Hz)
-        - air_pct (6k-20k Hz)
+        - presence_pct (4k-8k Hz)
+        - air_pct (8k-20k Hz)
 
         Returns:
             Dictionary mapping band index to gain in dB
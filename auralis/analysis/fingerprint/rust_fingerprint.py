@@ -23,18 +23,17 @@ Rust → schema differences handled here:
   a pre-existing inconsistency in that helper).
 - ``dynamic_range_variation`` raw dB-std → 0-1 via ``/ DRV_NORMALIZATION_DB`` (6 dB),
   matching ``VariationMetrics.calculate_from_crest_factors``.
-- ``bass_mid_ratio`` Rust fraction ``bass/(bass+mid)`` → schema dB via
-  ``10·log10(f/(1-f))``.
 
-Every other dimension (lufs, crest_db, tempo_bpm, rhythm_stability, transient_density,
-silence_ratio, spectral_flatness, harmonic_ratio, pitch_stability, chroma_energy,
-peak_consistency, stereo_width, phase_correlation) passes through unchanged.
+Every other dimension (lufs, crest_db, bass_mid_ratio, tempo_bpm, rhythm_stability,
+transient_density, silence_ratio, spectral_flatness, harmonic_ratio, pitch_stability,
+chroma_energy, peak_consistency, stereo_width, phase_correlation) passes through
+unchanged. ``bass_mid_ratio`` is already dB (``10·log10`` of bass/mid band power) in
+both the Rust crate and the schema.
 
 :copyright: (C) 2024 Auralis Team
 :license: GPLv3, see LICENSE for more details.
 """
 
-import math
 from typing import Any
 
 from .schema import CENTROID_NORMALIZATION_HZ
@@ -59,8 +58,8 @@ _BAND_KEY_MAP = {
 
 # Dimensions that carry over unchanged (same key, same units).
 _PASSTHROUGH = (
-    "lufs", "crest_db", "tempo_bpm", "rhythm_stability", "transient_density",
-    "silence_ratio", "spectral_flatness", "harmonic_ratio", "pitch_stability",
+    "lufs", "crest_db", "bass_mid_ratio", "tempo_bpm", "rhythm_stability",
+    "transient_density", "silence_ratio", "spectral_flatness", "harmonic_ratio", "pitch_stability",
     "chroma_energy", "peak_consistency", "stereo_width", "phase_correlation",
 )
 
@@ -103,12 +102,6 @@ def rust_fingerprint_to_schema(raw: dict[str, Any]) -> dict[str, float]:
     # Loudness variation: rename only (already dB in 0-10).
     out["loudness_variation_std"] = float(raw["loudness_variation"])
 
-    # Bass/mid ratio: Rust fraction bass/(bass+mid) in [0,1] -> schema dB.
-    f = float(raw["bass_mid_ratio"])
-    f = min(max(f, 1e-6), 1.0 - 1e-6)  # avoid log(0)/div0 at the extremes
-    bass_mid_db = 10.0 * math.log10(f / (1.0 - f))
-    out["bass_mid_ratio"] = max(-60.0, min(60.0, bass_mid_db))
-
     return out
 
 
@@ -1,5 +1,4 @@
 pub mod analyzer;
-pub mod rhythm;
-pub mod yin;
+pub mod streaming;
 
-pub use analyzer::analyze_fingerprint;
+pub use streaming::StreamingAnalysis;
@@ -0,0 +1,294 @@
+//! Memory-bounded analysis for very long files.
+//!
+//! The 25D fingerprint needs the whole decoded signal (the DSP crate
+//! resamples it, runs HPSS and a whole-track spectrum), which for an
+//! hour-long file is gigabytes per concurrent request. This analyzer is fed
+//! the decoder's packets as they arrive (`audio::loader::stream_audio`) and
+//! keeps only online accumulators: level statistics, frame-averaged
+//! spectral features, a BS.1770 `LoudnessMeter` (ten gating sums per
+//! second) and a streaming onset detector. Its memory does not grow with the samples, and
+//! the result does not depend on how the stream is split into blocks.
+
+use crate::audio::loader::SampleSink;
+use crate::error::{FingerprintError, Result};
+use super::analyzer::CancelToken;
+use auralis_dsp::loudness::LoudnessMeter;
+use auralis_dsp::onset_detector::OnsetDetector;
+use auralis_dsp::{compute_spectral_centroid, compute_spectral_flatness, compute_spectral_rolloff, magnitude_frames};
+use serde::{Deserialize, Serialize};
+
+/// STFT frame and hop for the spectral features
+const FRAME_SIZE: usize = 2048;
+const HOP_LENGTH: usize = 1024;
+
+/// Onset detector frame and hop (as `OnsetDetector`'s own tests use)
+const ONSET_FFT_SIZE: usize = 2048;
+const ONSET_HOP_LENGTH: usize = 512;
+
+/// Summary of a stream-analyzed track
+///
+/// Levels are in dBFS; loudness is `None` when no 400 ms block passes the
+/// BS.1770 gates (silence, or under 400 ms of audio), spectral features are
+/// 0 when the track is shorter than one frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingAnalysis {
+    pub duration_sec: f64,
+    /// BS.1770 gated integrated loudness (LUFS)
+    pub integrated_lufs: Option<f64>,
+    pub rms_db: f64,
+    pub peak_db: f64,
+    pub crest_db: f64,
+    /// Frame-averaged spectral centroid (Hz)
+    pub spectral_centroid_hz: f64,
+    /// Frame-averaged 85% spectral rolloff (Hz)
+    pub spectral_rolloff_hz: f64,
+    /// Frame-averaged spectral flatness (0-1)
+    pub spectral_flatness: f64,
+    pub onset_count: usize,
+    pub onsets_per_sec: f64,
+}
+
+/// Online feature accumulators fed block by block
+pub struct StreamingAnalyzer {
+    sample_rate: u32,
+    samples_seen: u64,
+    peak: f64,
+    sum_sq: f64,
+
+    // Spectral features over FRAME_SIZE/HOP_LENGTH frames
+    freqs: Vec<f32>,
+    /// Samples not yet consumed by a full frame
+    pending: Vec<f32>,
+    spectral_frames: u64,
+    centroid_sum: f64,
+    rolloff_sum: f64,
+    flatness_sum: f64,
+
+    loudness: LoudnessMeter,
+
+    onsets: OnsetDetector,
+    onset_count: usize,
+}
+
+impl StreamingAnalyzer {
+    pub fn new(sample_rate: u32) -> Self {
+        let bin_hz = sample_rate as f32 / FRAME_SIZE as f32;
+        Self {
+            sample_rate,
+            samples_seen: 0,
+            peak: 0.0,
+            sum_sq: 0.0,
+            freqs: (0..FRAME_SIZE / 2 + 1).map(|k| k as f32 * bin_hz).collect(),
+            pending: Vec::with_capacity(2 * FRAME_SIZE),
+            spectral_frames: 0,
+            centroid_sum: 0.0,
+            rolloff_sum: 0.0,
+            flatness_sum: 0.0,
+            loudness: LoudnessMeter::new(sample_rate),
+            onsets: OnsetDetector::new(sample_rate as f64, ONSET_FFT_SIZE, ONSET_HOP_LENGTH),
+            onset_count: 0,
+        }
+    }
+
+    /// Feed the next block of mono samples
+    pub fn push(&mut self, block: &[f64]) {
+        self.samples_seen += block.len() as u64;
+        for &x in block {
+            self.peak = self.peak.max(x.abs());
+            self.sum_sq += x * x;
+        }
+
+        self.loudness.push(block);
+        self.onset_count += self.onsets.process_block(block).len();
+
+        // Every complete frame in the pending buffer; the next frame starts
+        // one hop after the last, so drop everything before it
+        self.pending.extend(block.iter().map(|&x| x as f32));
+        let frames = magnitude_frames(&self.pending, FRAME_SIZE, HOP_LENGTH);
+        for magnitudes in &frames {
+            let psd: Vec<f32> = magnitudes.iter().map(|m| m * m).collect();
+            self.spectral_frames += 1;
+            self.centroid_sum += compute_spectral_centroid(&psd, &self.freqs) as f64;
+            self.rolloff_sum += compute_spectral_rolloff(&psd, &self.freqs, 0.85) as f64;
+            self.flatness_sum += compute_spectral_flatness(&psd) as f64;
+        }
+        self.pending.drain(..frames.len() * HOP_LENGTH);
+    }
+
+    /// Summarize everything pushed so far
+    pub fn finish(&self) -> StreamingAnalysis {
+        let duration_sec = self.samples_seen as f64 / self.sample_rate as f64;
+        let to_db = |x: f64| if x > 0.0 { 20.0 * x.log10() } else { -120.0 };
+        let rms = if self.samples_seen > 0 {
+            (self.sum_sq / self.samples_seen as f64).sqrt()
+        } else {
+            0.0
+        };
+        let lufs = self.loudness.integrated();
+        let frame_mean = |sum: f64| {
+            if self.spectral_frames > 0 {
+                sum / self.spectral_frames as f64
+            } else {
+                0.0
+            }
+        };
+
+        StreamingAnalysis {
+            duration_sec,
+            integrated_lufs: lufs.is_finite().then_some(lufs),
+            rms_db: to_db(rms),
+            peak_db: to_db(self.peak),
+            crest_db: if rms > 0.0 { to_db(self.peak) - to_db(rms) } else { 0.0 },
+            spectral_centroid_hz: frame_mean(self.centroid_sum),
+            spectral_rolloff_hz: frame_mean(self.rolloff_sum),
+            spectral_flatness: frame_mean(self.flatness_sum),
+            onset_count: self.onset_count,
+            onsets_per_sec: if duration_sec > 0.0 { self.onset_count as f64 / duration_sec } else { 0.0 },
+        }
+    }
+}
+
+/// `SampleSink` running a `StreamingAnalyzer`, cancellable between packets
+pub struct StreamingSink<'a> {
+    analyzer: Option<StreamingAnalyzer>,
+    channels: u16,
+    cancel: &'a CancelToken,
+}
+
+impl<'a> StreamingSink<'a> {
+    pub fn new(cancel: &'a CancelToken) -> Self {
+        Self { analyzer: None, channels: 0, cancel }
+    }
+
+    /// Sample rate, channel count and summary of the decoded stream
+    pub fn finish(&self) -> Result<(u32, u16, StreamingAnalysis)> {
+        let analyzer = self
+            .analyzer
+            .as_ref()
+            .ok_or_else(|| FingerprintError::InvalidAudio("No audio samples decoded".to_string()))?;
+        Ok((analyzer.sample_rate, self.channels, analyzer.finish()))
+    }
+}
+
+impl SampleSink for StreamingSink<'_> {
+    fn start(&mut self, sample_rate: u32, channels: u16) -> Result<()> {
+        if sample_rate == 0 {
+            return Err(FingerprintError::InvalidAudio("Sample rate is 0".to_string()));
+        }
+        self.analyzer = Some(StreamingAnalyzer::new(sample_rate));
+        self.channels = channels;
+        Ok(())
+    }
+
+    fn push(&mut self, block: &[f64]) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(FingerprintError::Timeout("analysis cancelled".to_string()));
+        }
+        if let Some(analyzer) = self.analyzer.as_mut() {
+            analyzer.push(block);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::loader::{load_audio, stream_audio};
+    use crate::test_support::test_wav_bytes;
+
+    /// Streaming straight from the decoder must agree with batch analysis:
+    /// the DSP crate's whole-signal functions over the fully loaded samples
+    #[tokio::test]
+    async fn streaming_matches_batch() {
+        let path = std::env::temp_dir().join(format!("fingerprint-server-{}-streaming.wav", std::process::id()));
+        std::fs::write(&path, test_wav_bytes(22_050, 30.0)).unwrap();
+        let path_str = path.to_str().unwrap().to_string();
+
+        let cancel = CancelToken::new();
+        let mut sink = StreamingSink::new(&cancel);
+        stream_audio(&path_str, &mut sink).unwrap();
+        let (sample_rate, channels, streamed) = sink.finish().unwrap();
+
+        let loaded = load_audio(&path_str).await.unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!((sample_rate, channels), (loaded.sample_rate, loaded.channels));
+        let samples: Vec<f32> = loaded.samples.iter().map(|&x| x as f32).collect();
+
+        let close = |a: f64, b: f64, tolerance: f64, name: &str| {
+            assert!((a - b).abs() <= tolerance * b.abs().max(1.0), "{}: streamed {} vs batch {}", name, a, b);
+        };
+
+        // Levels over the whole signal
+        let n = loaded.samples.len() as f64;
+        let rms = (loaded.samples.iter().map(|x| x * x).sum::<f64>() / n).sqrt();
+        let peak = loaded.samples.iter().fold(0.0f64, |m, x| m.max(x.abs()));
+        close(streamed.duration_sec, 30.0, 1e-6, "duration");
+        close(streamed.rms_db, 20.0 * rms.log10(), 1e-6, "rms_db");
+        close(streamed.peak_db, 20.0 * peak.log10(), 1e-6, "peak_db");
+        close(streamed.crest_db, 20.0 * (peak / rms).log10(), 1e-6, "crest_db");
+
+        // Spectral features: every frame of the whole signal at once, averaged
+        let bin_hz = sample_rate as f32 / FRAME_SIZE as f32;
+        let freqs: Vec<f32> = (0..FRAME_SIZE / 2 + 1).map(|k| k as f32 * bin_hz).collect();
+        let psds: Vec<Vec<f32>> = magnitude_frames(&samples, FRAME_SIZE, HOP_LENGTH)
+            .iter()
+            .map(|frame| frame.iter().map(|m| m * m).collect())
+            .collect();
+        let mean = |feature: &dyn Fn(&[f32]) -> f32| psds.iter().map(|psd| feature(psd) as f64).sum::<f64>() / psds.len() as f64;
+        close(streamed.spectral_centroid_hz, mean(&|psd| compute_spectral_centroid(psd, &freqs)), 1e-3, "centroid");
+        close(streamed.spectral_rolloff_hz, mean(&|psd| compute_spectral_rolloff(psd, &freqs, 0.85)), 1e-3, "rolloff");
+        close(streamed.spectral_flatness, mean(&compute_spectral_flatness), 1e-3, "flatness");
+
+        // Onsets: the streaming detector thresholds against the running
+        // maximum rather than the global one, so it can accept a few early
+        // peaks the batch pass rejects. Both must find the clicks, two per
+        // second, and agree within 15%.
+        let detector = OnsetDetector::new(sample_rate as f64, ONSET_FFT_SIZE, ONSET_HOP_LENGTH);
+        let batch_onsets = detector.detect(&ndarray::ArrayView1::from(&loaded.samples[..])).onset_frames.len();
+        let batch_rate = batch_onsets as f64 / 30.0;
+        for rate in [streamed.onsets_per_sec, batch_rate] {
+            assert!((rate - 2.0).abs() <= 0.2, "{} onsets/s for clicks at 2/s", rate);
+        }
+        assert!(
+            (streamed.onsets_per_sec - batch_rate).abs() <= 0.15 * batch_rate,
+            "streamed {} onsets/s vs batch {}",
+            streamed.onsets_per_sec,
+            batch_rate
+        );
+
+        let reference = auralis_dsp::integrated_loudness(&samples, loaded.sample_rate);
+        let lufs = streamed.integrated_lufs.unwrap();
+        assert!((lufs - reference).abs() < 0.01, "streamed {} LUFS vs batch {}", lufs, reference);
+    }
+
+    /// Splitting the stream differently must not change anything
+    #[test]
+    fn block_size_does_not_change_result() {
+        let samples: Vec<f64> = (0..50_000).map(|i| ((i as f64 * 0.037).sin() + (i % 4000 == 0) as u8 as f64) * 0.5).collect();
+        let mut whole = StreamingAnalyzer::new(22_050);
+        whole.push(&samples);
+        let mut blocks = StreamingAnalyzer::new(22_050);
+        samples.chunks(777).for_each(|block| blocks.push(block));
+        let (whole, blocks) = (whole.finish(), blocks.finish());
+
+        assert_eq!(whole.onset_count, blocks.onset_count);
+        for (a, b) in [
+            (whole.rms_db, blocks.rms_db),
+            (whole.spectral_centroid_hz, blocks.spectral_centroid_hz),
+            (whole.spectral_rolloff_hz, blocks.spectral_rolloff_hz),
+            (whole.spectral_flatness, blocks.spectral_flatness),
+        ] {
+            assert!((a - b).abs() <= 1e-6 * a.abs().max(1.0), "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn cancelled_stream_stops() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let mut sink = StreamingSink::new(&cancel);
+        sink.start(22_050, 1).unwrap();
+        assert!(matches!(sink.push(&[0.0; 64]), Err(FingerprintError::Timeout(_))));
+    }
+}
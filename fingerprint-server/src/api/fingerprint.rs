@@ -1,15 +1,18 @@
-use axum::{Json, http::StatusCode, body::to_bytes};
-use std::time::Instant;
-use crate::models::request::{FingerprintRequest, FingerprintResponse, AudioMetadata};
-use crate::audio::loader::load_audio;
-use crate::analysis::analyzer::analyze_fingerprint;
+use axum::{Json, http::{HeaderMap, StatusCode}, body::{to_bytes, Bytes}, extract::{Query, State}};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use crate::models::request::{FingerprintRequest, FingerprintResponse, AudioMetadata, RawFingerprintParams};
+use crate::audio::loader::{file_format, load_audio, load_audio_bytes, normalize_format_name, AudioData};
+use crate::audio::tags::read_tags_async;
+use crate::analysis::analyzer::{analyze_fingerprint, CancelToken};
 use crate::error::{Result, FingerprintError};
+use super::limiter::ConcurrencyLimiter;
+use super::validation::{allowed_root, validate_filepath};
 
 pub async fn fingerprint_handler(
+    State(limiter): State<ConcurrencyLimiter>,
     body: axum::body::Body,
 ) -> Result<(StatusCode, Json<FingerprintResponse>)> {
-    let start = Instant::now();
-
     // Manually parse body to avoid Content-Type strictness issues
     let bytes = to_bytes(body, usize::MAX)
         .await
@@ -18,11 +21,139 @@ pub async fn fingerprint_handler(
     let req: FingerprintRequest = serde_json::from_slice(&bytes)
         .map_err(|e| FingerprintError::InvalidAudio(format!("Failed to parse JSON: {}", e)))?;
 
+    // Held until the response is built; refuses the request before loading
+    // any audio when the server is saturated
+    let _permit = limiter.try_acquire()?;
+    let response = fingerprint_track(req, |_| {}, CancelToken::new()).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Default per-request analysis deadline
+pub const DEFAULT_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(300);
+
+static ANALYSIS_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Per-request analysis deadline, from `FINGERPRINT_ANALYSIS_TIMEOUT_SECS`
+/// (read once) or `DEFAULT_ANALYSIS_TIMEOUT`
+pub fn analysis_timeout() -> Duration {
+    *ANALYSIS_TIMEOUT.get_or_init(|| {
+        crate::config::duration_from_env("FINGERPRINT_ANALYSIS_TIMEOUT_SECS", DEFAULT_ANALYSIS_TIMEOUT)
+    })
+}
+
+/// Maximum accepted body size for `POST /fingerprint/raw` (512 MiB, enough
+/// for ~50 minutes of 24-bit/96 kHz stereo WAV)
+pub const MAX_RAW_BODY_BYTES: usize = 512 * 1024 * 1024;
+
+/// Header carrying the container format of a raw upload ("wav", "flac", ...)
+pub const FORMAT_HINT_HEADER: &str = "x-audio-format";
+
+/// `POST /fingerprint/raw?track_id=N`: fingerprint audio file bytes sent as
+/// the request body, for clients that don't share a filesystem with the server
+///
+/// The container format is taken from the `X-Audio-Format` header when
+/// present, otherwise probed from the bytes.
+pub async fn fingerprint_raw_handler(
+    State(limiter): State<ConcurrencyLimiter>,
+    Query(params): Query<RawFingerprintParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<FingerprintResponse>)> {
+    let start = Instant::now();
+
+    let format_hint = headers
+        .get(FORMAT_HINT_HEADER)
+        .map(|value| {
+            value.to_str().map(str::to_string).map_err(|_| {
+                FingerprintError::InvalidAudio(format!("Invalid {} header", FORMAT_HINT_HEADER))
+            })
+        })
+        .transpose()?;
+
+    tracing::debug!(
+        "Processing raw fingerprint request for track {}: {} bytes",
+        params.track_id,
+        body.len()
+    );
+
+    let _permit = limiter.try_acquire()?;
+    let audio_data = load_audio_bytes(body.to_vec(), format_hint.clone()).await?;
+    let format = format_hint
+        .as_deref()
+        .map(normalize_format_name)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let response = fingerprint_audio(
+        params.track_id,
+        audio_data,
+        format,
+        start,
+        analysis_timeout(),
+        |_| {},
+        CancelToken::new(),
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Load and fingerprint the file named by `req`
+///
+/// Shared by the HTTP and WebSocket routes. The path is validated (and
+/// resolved) by `validate_filepath` before anything is read. `on_progress` is
+/// called from the blocking analysis thread with the percentage of analysis
+/// completed. With `req.extract_tags`, tags and cover-art details are read
+/// from the container headers after decoding; no audio is decoded twice.
+/// Cancelling `cancel` (e.g. when a WebSocket client goes away) stops the
+/// analysis at its next checkpoint.
+pub async fn fingerprint_track<F>(
+    req: FingerprintRequest,
+    on_progress: F,
+    cancel: CancelToken,
+) -> Result<FingerprintResponse>
+where
+    F: FnMut(u8) + Send + 'static,
+{
+    let start = Instant::now();
+
     tracing::debug!("Processing fingerprint request for track {}: {}", req.track_id, req.filepath);
 
+    let path = validate_filepath(&req.filepath, allowed_root())?;
+
     // Load audio asynchronously (I/O bound)
-    let audio_data = load_audio(&req.filepath).await?;
+    let audio_data = load_audio(&path.to_string_lossy()).await?;
+    let format = file_format(&path);
+    let tags = if req.extract_tags {
+        Some(read_tags_async(&path.to_string_lossy()).await?)
+    } else {
+        None
+    };
+
+    let mut response =
+        fingerprint_audio(req.track_id, audio_data, format, start, analysis_timeout(), on_progress, cancel).await?;
+    response.tags = tags;
+    Ok(response)
+}
 
+/// Analyze decoded audio and build the response (shared by all routes)
+///
+/// Fails with `FingerprintError::Timeout` (HTTP 504) if analysis takes
+/// longer than `deadline`; the blocking analysis is then cancelled through
+/// `cancel` at its next checkpoint so it releases its thread. Callers may
+/// cancel it themselves for the same effect.
+async fn fingerprint_audio<F>(
+    track_id: u32,
+    audio_data: AudioData,
+    format: String,
+    start: Instant,
+    deadline: Duration,
+    on_progress: F,
+    cancel: CancelToken,
+) -> Result<FingerprintResponse>
+where
+    F: FnMut(u8) + Send + 'static,
+{
     tracing::debug!(
         "Loaded audio: {} samples at {} Hz, {} channels",
         audio_data.samples.len(),
@@ -32,19 +163,43 @@ pub async fn fingerprint_handler(
 
     // Analyze fingerprint (CPU bound, spawn blocking to not block async runtime)
     let audio_data_clone = audio_data.clone();
-    let fingerprint = tokio::task::spawn_blocking(move || {
-        analyze_fingerprint(&audio_data_clone.samples, audio_data_clone.sample_rate)
-    })
-    .await
-    .map_err(|e| crate::error::FingerprintError::AnalysisError(format!("Task join error: {}", e)))??;
+    let task_cancel = cancel.clone();
+    let analysis = tokio::task::spawn_blocking(move || {
+        let fingerprint = analyze_fingerprint(
+            &audio_data_clone.samples,
+            audio_data_clone.sample_rate,
+            on_progress,
+            &task_cancel,
+        )?;
+        let samples: Vec<f32> = audio_data_clone.samples.iter().map(|&x| x as f32).collect();
+        let true_peak_dbtp = auralis_dsp::true_peak_dbtp(&samples, audio_data_clone.sample_rate) as f64;
+        Ok::<_, FingerprintError>((fingerprint, true_peak_dbtp))
+    });
+
+    let (fingerprint, true_peak_dbtp) = match tokio::time::timeout(deadline, analysis).await {
+        Ok(joined) => joined
+            .map_err(|e| crate::error::FingerprintError::AnalysisError(format!("Task join error: {}", e)))??,
+        Err(_) => {
+            cancel.cancel();
+            tracing::warn!(
+                "Analysis of track {} exceeded {:.1}s deadline, cancelling",
+                track_id,
+                deadline.as_secs_f64()
+            );
+            return Err(FingerprintError::Timeout(format!(
+                "analysis exceeded {:.1}s deadline",
+                deadline.as_secs_f64()
+            )));
+        }
+    };
 
     let valid_dims = fingerprint.valid_dimensions();
-    tracing::debug!("Fingerprint analysis complete for track {}: {}/25 valid dimensions", req.track_id, valid_dims);
+    tracing::debug!("Fingerprint analysis complete for track {}: {}/25 valid dimensions", track_id, valid_dims);
 
     // CRITICAL: Check if fingerprint is invalid (has NaN or zero dimensions)
     if !fingerprint.is_valid() {
-        tracing::error!("CRITICAL: Fingerprint analysis returned INVALID for track {}: {} only {}/25 dimensions - {}",
-            req.track_id, req.filepath, valid_dims, req.filepath);
+        tracing::error!("CRITICAL: Fingerprint analysis returned INVALID for track {} ({}): only {}/25 dimensions",
+            track_id, format, valid_dims);
         return Err(crate::error::FingerprintError::AnalysisError(
             format!("Fingerprint invalid: only {}/25 dimensions", valid_dims)
         ));
@@ -54,30 +209,238 @@ pub async fn fingerprint_handler(
     let duration_sec = audio_data.samples.len() as f64 / audio_data.sample_rate as f64;
 
     let response = FingerprintResponse {
-        track_id: req.track_id,
+        track_id,
         fingerprint,
         metadata: AudioMetadata {
             duration_sec,
             sample_rate: audio_data.sample_rate,
             channels: audio_data.channels,
-            format: infer_format(&req.filepath),
+            format,
+            codec: audio_data.codec,
+            decode: audio_data.decode,
+            true_peak_dbtp: Some(true_peak_dbtp),
         },
+        tags: None,
         processing_time_ms: start.elapsed().as_millis(),
     };
 
     tracing::info!(
         "Successfully fingerprinted track {} in {}ms",
-        req.track_id,
+        track_id,
         response.processing_time_ms
     );
 
-    Ok((StatusCode::OK, Json(response)))
+    Ok(response)
 }
 
-fn infer_format(filepath: &str) -> String {
-    filepath
-        .split('.')
-        .last()
-        .unwrap_or("unknown")
-        .to_lowercase()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_wav_bytes;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn post_raw(body: Vec<u8>, format: Option<&str>) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::post("/fingerprint/raw?track_id=42");
+        if let Some(format) = format {
+            request = request.header(FORMAT_HINT_HEADER, format);
+        }
+        let response = crate::build_router()
+            .oneshot(request.body(Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    async fn post_json(body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let response = crate::build_router()
+            .oneshot(
+                Request::post("/fingerprint")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn traversal_path_is_rejected() {
+        let (status, json) = post_json(serde_json::json!({
+            "track_id": 1,
+            "filepath": "/music/../../etc/shadow.flac",
+        }))
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("'..'"), "response: {}", json);
+    }
+
+    #[tokio::test]
+    async fn unsupported_extension_is_rejected() {
+        let path = std::env::temp_dir().join(format!("fingerprint-server-{}-notes.txt", std::process::id()));
+        std::fs::write(&path, "not audio").unwrap();
+
+        let (status, json) = post_json(serde_json::json!({
+            "track_id": 1,
+            "filepath": path.to_str().unwrap(),
+        }))
+        .await;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("extension"), "response: {}", json);
+    }
+
+    #[tokio::test]
+    async fn extensionless_wav_is_accepted_by_content() {
+        let dir = std::env::temp_dir();
+        let wav = dir.join(format!("fingerprint-server-{}-extensionless", std::process::id()));
+        let text = dir.join(format!("fingerprint-server-{}-extensionless-text", std::process::id()));
+        std::fs::write(&wav, test_wav_bytes(22_050, 3.0)).unwrap();
+        std::fs::write(&text, "not audio at all").unwrap();
+
+        let mut responses = Vec::new();
+        for (route, path) in [("/fingerprint", &wav), ("/probe", &wav), ("/analyze/stream", &wav), ("/fingerprint", &text)] {
+            let body = serde_json::json!({ "track_id": 3, "filepath": path.to_str().unwrap() }).to_string();
+            let response = crate::build_router()
+                .oneshot(Request::post(route).body(Body::from(body)).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            responses.push((route, status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()));
+        }
+        std::fs::remove_file(&wav).ok();
+        std::fs::remove_file(&text).ok();
+
+        for (route, status, json) in &responses[..3] {
+            assert_eq!(*status, StatusCode::OK, "{}: {}", route, json);
+        }
+        assert_eq!(responses[0].2["metadata"]["format"], "wav");
+        assert_eq!(responses[1].2["format"], "wav");
+        assert_eq!(responses[2].2["metadata"]["format"], "wav");
+        let (_, status, json) = &responses[3];
+        assert_eq!(*status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("signature"), "response: {}", json);
+    }
+
+    #[tokio::test]
+    async fn requests_beyond_limit_get_503() {
+        let path = crate::test_support::write_test_wav("concurrency-limit");
+        let router = crate::build_router_with_limiter(ConcurrencyLimiter::new(2));
+        let body = serde_json::json!({ "track_id": 5, "filepath": path.to_str().unwrap() }).to_string();
+
+        let requests: Vec<_> = (0..5)
+            .map(|_| {
+                let request = Request::post("/fingerprint").body(Body::from(body.clone())).unwrap();
+                tokio::spawn(router.clone().oneshot(request))
+            })
+            .collect();
+
+        let mut statuses = Vec::new();
+        for request in requests {
+            statuses.push(request.await.unwrap().unwrap().status());
+        }
+        std::fs::remove_file(&path).ok();
+
+        let ok = statuses.iter().filter(|s| **s == StatusCode::OK).count();
+        let busy = statuses.iter().filter(|s| **s == StatusCode::SERVICE_UNAVAILABLE).count();
+        assert_eq!((ok, busy), (2, 3), "statuses: {:?}", statuses);
+    }
+
+    #[tokio::test]
+    async fn raw_wav_bytes_are_fingerprinted() {
+        let (status, json) = post_raw(test_wav_bytes(22_050, 3.0), Some("wav")).await;
+
+        assert_eq!(status, StatusCode::OK, "response: {}", json);
+        assert_eq!(json["track_id"], 42);
+        assert_eq!(json["metadata"]["format"], "wav");
+        assert_eq!(json["metadata"]["sample_rate"], 22_050);
+        assert!((json["metadata"]["duration_sec"].as_f64().unwrap() - 3.0).abs() < 1e-3);
+        // Tone plus clicks stays under 0.72 of full scale (-2.9 dBFS)
+        let true_peak = json["metadata"]["true_peak_dbtp"].as_f64().unwrap();
+        assert!((-6.0..-2.8).contains(&true_peak), "true peak {} dBTP", true_peak);
+        assert!(json["fingerprint"]["lufs"].is_number());
+    }
+
+    #[tokio::test]
+    async fn tags_are_returned_when_requested() {
+        let cover = vec![0x55; 2048];
+        let bytes = crate::test_support::test_flac_bytes_with(
+            22_050,
+            3.0,
+            &crate::test_support::FlacOptions {
+                comments: &[("TITLE", "Chord"), ("ARTIST", "Auralis"), ("ALBUM", "Fixtures")],
+                picture: Some(("image/jpeg", 300, 300, &cover)),
+                ..Default::default()
+            },
+        );
+        let path = std::env::temp_dir().join(format!("fingerprint-server-{}-tagged.flac", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+
+        let (status, json) = post_json(serde_json::json!({
+            "track_id": 3,
+            "filepath": path.to_str().unwrap(),
+            "extract_tags": true,
+        }))
+        .await;
+        let (_, untagged) = post_json(serde_json::json!({ "track_id": 3, "filepath": path.to_str().unwrap() })).await;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(status, StatusCode::OK, "response: {}", json);
+        assert_eq!(json["tags"]["title"], "Chord");
+        assert_eq!(json["tags"]["artist"], "Auralis");
+        assert_eq!(json["tags"]["album"], "Fixtures");
+        assert_eq!(json["tags"]["cover_art"]["media_type"], "image/jpeg");
+        assert_eq!(json["tags"]["cover_art"]["size_bytes"], 2048);
+        assert!(untagged.get("tags").is_none(), "response: {}", untagged);
+    }
+
+    #[tokio::test]
+    async fn raw_format_is_probed_without_hint() {
+        let (status, json) = post_raw(test_wav_bytes(22_050, 1.0), None).await;
+        assert_eq!(status, StatusCode::OK, "response: {}", json);
+        assert_eq!(json["metadata"]["format"], "unknown");
+    }
+
+    #[tokio::test]
+    async fn analysis_past_deadline_times_out() {
+        let bytes = test_wav_bytes(22_050, 3.0);
+        let audio_data = load_audio_bytes(bytes, Some("wav".to_string())).await.unwrap();
+        let cancel = CancelToken::new();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(30),
+            fingerprint_audio(
+                9,
+                audio_data,
+                "wav".to_string(),
+                Instant::now(),
+                Duration::from_millis(1),
+                |_| {},
+                cancel.clone(),
+            ),
+        )
+        .await
+        .expect("timed-out request must return promptly");
+
+        match result {
+            Err(FingerprintError::Timeout(msg)) => assert!(msg.contains("deadline")),
+            other => panic!("expected timeout, got {:?}", other.map(|r| r.track_id)),
+        }
+        assert!(cancel.is_cancelled(), "the caller's token must be cancelled");
+    }
+
+    #[tokio::test]
+    async fn raw_empty_body_is_rejected() {
+        let (status, json) = post_raw(Vec::new(), Some("wav")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("Empty"));
+    }
 }
@@ -1,10 +1,11 @@
-use axum::Json;
+use axum::{extract::State, Json};
+use crate::api::limiter::ConcurrencyLimiter;
 use crate::models::request::HealthResponse;
 use std::sync::OnceLock;
 
 static START_TIME: OnceLock<std::time::Instant> = OnceLock::new();
 
-pub async fn health_handler() -> Json<HealthResponse> {
+pub async fn health_handler(State(limiter): State<ConcurrencyLimiter>) -> Json<HealthResponse> {
     let start = START_TIME.get_or_init(std::time::Instant::now);
     let uptime = start.elapsed().as_secs();
 
@@ -12,5 +13,31 @@ pub async fn health_handler() -> Json<HealthResponse> {
         status: "healthy".to_string(),
         version: "0.1.0".to_string(),
         uptime_sec: uptime,
+        in_flight: limiter.in_flight(),
+        max_concurrent: limiter.limit(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn reports_in_flight() {
+        let limiter = ConcurrencyLimiter::new(4);
+        let _permit = limiter.try_acquire().unwrap();
+
+        let response = crate::build_router_with_limiter(limiter)
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(json["in_flight"], 1);
+        assert_eq!(json["max_concurrent"], 4);
+    }
+}
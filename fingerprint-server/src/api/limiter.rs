@@ -0,0 +1,55 @@
+//! Bound on concurrent fingerprint requests.
+//!
+//! Each request decodes a whole track into memory and occupies a blocking
+//! thread for the analysis. Without a bound a burst of requests decodes
+//! everything at once; with one, requests beyond the limit are refused with
+//! a 503 before any audio is loaded, and clients retry.
+
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::error::{FingerprintError, Result};
+
+/// Default number of requests analyzed at once (one per Python worker)
+pub const DEFAULT_MAX_CONCURRENT: usize = 32;
+
+/// Concurrent-request limit, from `FINGERPRINT_MAX_CONCURRENT` or
+/// `DEFAULT_MAX_CONCURRENT`
+pub fn max_concurrent_from_env() -> usize {
+    crate::config::count_from_env("FINGERPRINT_MAX_CONCURRENT", DEFAULT_MAX_CONCURRENT).max(1)
+}
+
+/// Shared semaphore handing out one slot per in-flight request
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimiter {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit,
+        }
+    }
+
+    /// Take a slot for the lifetime of the returned permit, or fail with
+    /// `FingerprintError::Overloaded` (HTTP 503) if all are in use
+    pub fn try_acquire(&self) -> Result<OwnedSemaphorePermit> {
+        self.semaphore.clone().try_acquire_owned().map_err(|_| {
+            FingerprintError::Overloaded(format!(
+                "{} requests already in progress, retry later",
+                self.limit
+            ))
+        })
+    }
+
+    /// Number of requests currently holding a slot
+    pub fn in_flight(&self) -> usize {
+        self.limit - self.semaphore.available_permits()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
@@ -1,2 +1,7 @@
 pub mod health;
 pub mod fingerprint;
+pub mod limiter;
+pub mod probe;
+pub mod streaming;
+pub mod ws;
+pub mod validation;
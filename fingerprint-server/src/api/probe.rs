@@ -0,0 +1,70 @@
+use axum::{Json, http::StatusCode, body::to_bytes};
+use std::time::Instant;
+use crate::models::request::{ProbeRequest, ProbeResponse};
+use crate::audio::loader::{file_format, probe_audio};
+use crate::error::{Result, FingerprintError};
+use super::validation::{allowed_root, validate_filepath};
+
+/// `POST /probe`: duration, sample rate, channels and codec of a file
+///
+/// Reads the container headers only, so clients can decide whether a file is
+/// worth fingerprinting without paying for a full decode. Not subject to the
+/// concurrency limit: a probe costs a few reads, not an analysis.
+pub async fn probe_handler(body: axum::body::Body) -> Result<(StatusCode, Json<ProbeResponse>)> {
+    let start = Instant::now();
+
+    // Manually parse body to avoid Content-Type strictness issues
+    let bytes = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| FingerprintError::InvalidAudio(format!("Failed to read request body: {}", e)))?;
+    let req: ProbeRequest = serde_json::from_slice(&bytes)
+        .map_err(|e| FingerprintError::InvalidAudio(format!("Failed to parse JSON: {}", e)))?;
+
+    let path = validate_filepath(&req.filepath, allowed_root())?;
+    let probe = probe_audio(&path.to_string_lossy()).await?;
+    let format = file_format(&path);
+
+    Ok((
+        StatusCode::OK,
+        Json(ProbeResponse {
+            format,
+            probe,
+            processing_time_ms: start.elapsed().as_millis(),
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use std::time::{Duration, Instant};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn probe_reports_duration_without_decoding() {
+        // Three hours of stereo 44.1 kHz: ~1.9 GB that a decode couldn't get
+        // through in the time allowed, but a sparse file costs no disk
+        let path = crate::test_support::write_sparse_wav("probe-large", 44_100, 2, 3.0 * 3600.0);
+        let body = serde_json::json!({ "filepath": path.to_str().unwrap() }).to_string();
+
+        let start = Instant::now();
+        let response = crate::build_router()
+            .oneshot(Request::post("/probe").body(Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        let elapsed = start.elapsed();
+        std::fs::remove_file(&path).ok();
+
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(status, StatusCode::OK, "response: {}", json);
+        assert!(elapsed < Duration::from_secs(2), "probe took {:?}", elapsed);
+        assert!((json["duration_sec"].as_f64().unwrap() - 10_800.0).abs() < 1e-6, "response: {}", json);
+        assert_eq!(json["sample_rate"], 44_100);
+        assert_eq!(json["channels"], 2);
+        assert_eq!(json["format"], "wav");
+        assert_eq!(json["codec"], "pcm_s16le");
+    }
+}
@@ -0,0 +1,125 @@
+use axum::{Json, http::StatusCode, body::to_bytes, extract::State};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use crate::models::request::{FingerprintRequest, StreamingAnalysisResponse, AudioMetadata};
+use crate::audio::loader::{file_format, stream_audio};
+use crate::analysis::analyzer::CancelToken;
+use crate::analysis::streaming::StreamingSink;
+use crate::error::{Result, FingerprintError};
+use super::fingerprint::analysis_timeout;
+use super::limiter::ConcurrencyLimiter;
+use super::validation::{allowed_root, validate_filepath};
+
+/// `POST /analyze/stream`: summary analysis of a file in bounded memory
+///
+/// Takes the same body as `POST /fingerprint`. The file is decoded packet by
+/// packet into online accumulators (`analysis::streaming`) and never held in
+/// full, so hour-long files cost no more memory than short ones. The result
+/// is a summary (loudness, levels, spectral averages, onset rate), not the
+/// 25D fingerprint, which needs the whole signal.
+pub async fn streaming_analysis_handler(
+    State(limiter): State<ConcurrencyLimiter>,
+    body: axum::body::Body,
+) -> Result<(StatusCode, Json<StreamingAnalysisResponse>)> {
+    let start = Instant::now();
+
+    // Manually parse body to avoid Content-Type strictness issues
+    let bytes = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| FingerprintError::InvalidAudio(format!("Failed to read request body: {}", e)))?;
+    let req: FingerprintRequest = serde_json::from_slice(&bytes)
+        .map_err(|e| FingerprintError::InvalidAudio(format!("Failed to parse JSON: {}", e)))?;
+
+    let _permit = limiter.try_acquire()?;
+    let path = validate_filepath(&req.filepath, allowed_root())?;
+    let response = analyze_file_streaming(req.track_id, path, start, analysis_timeout()).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Decode and analyze the validated `path` on a blocking thread, cancelling
+/// it if it runs past `deadline`
+async fn analyze_file_streaming(
+    track_id: u32,
+    path: PathBuf,
+    start: Instant,
+    deadline: Duration,
+) -> Result<StreamingAnalysisResponse> {
+    let format = file_format(&path);
+    let filepath = path.to_string_lossy().into_owned();
+
+    let cancel = CancelToken::new();
+    let task_cancel = cancel.clone();
+    let analysis = tokio::task::spawn_blocking(move || {
+        let mut sink = StreamingSink::new(&task_cancel);
+        let decoded = stream_audio(&filepath, &mut sink)?;
+        sink.finish().map(|summary| (decoded, summary))
+    });
+
+    let ((codec, decode), (sample_rate, channels, analysis)) = match tokio::time::timeout(deadline, analysis).await {
+        Ok(joined) => joined
+            .map_err(|e| FingerprintError::AnalysisError(format!("Task join error: {}", e)))??,
+        Err(_) => {
+            cancel.cancel();
+            tracing::warn!(
+                "Streaming analysis of track {} exceeded {:.1}s deadline, cancelling",
+                track_id,
+                deadline.as_secs_f64()
+            );
+            return Err(FingerprintError::Timeout(format!(
+                "analysis exceeded {:.1}s deadline",
+                deadline.as_secs_f64()
+            )));
+        }
+    };
+
+    let response = StreamingAnalysisResponse {
+        track_id,
+        metadata: AudioMetadata {
+            duration_sec: analysis.duration_sec,
+            sample_rate,
+            channels,
+            format,
+            codec,
+            decode,
+            true_peak_dbtp: None,
+        },
+        analysis,
+        processing_time_ms: start.elapsed().as_millis(),
+    };
+
+    tracing::info!(
+        "Stream-analyzed track {} in {}ms",
+        track_id,
+        response.processing_time_ms
+    );
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn stream_route_returns_summary() {
+        let path = crate::test_support::write_test_wav("stream-route");
+        let body = serde_json::json!({ "track_id": 7, "filepath": path.to_str().unwrap() }).to_string();
+        let response = crate::build_router()
+            .oneshot(Request::post("/analyze/stream").body(Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(status, StatusCode::OK, "response: {}", json);
+        assert_eq!(json["track_id"], 7);
+        assert_eq!(json["metadata"]["sample_rate"], 22_050);
+        assert!((json["analysis"]["duration_sec"].as_f64().unwrap() - 3.0).abs() < 1e-3);
+        assert!(json["analysis"]["integrated_lufs"].as_f64().unwrap() < 0.0);
+    }
+}
@@ -0,0 +1,189 @@
+//! Validation of client-supplied file paths.
+//!
+//! `POST /fingerprint` and the WebSocket route take a path on the server's
+//! filesystem. It is checked here before anything is opened, so malformed,
+//! traversing or non-audio paths fail fast with a 400 instead of deep inside
+//! the decoder. Files without an extension are accepted when their first
+//! bytes carry a known audio container signature (see
+//! `audio::loader::sniff_file`); formats without a fixed signature, such as
+//! an MP3 behind leading junk, need an extension.
+//!
+//! The server reads whatever path a client names, with the permissions of
+//! the server process. Binding to loopback only limits who can ask; on a
+//! shared host or in a container any local client could read any audio file
+//! the process can see. Setting an allowed root (`--allowed-root <dir>` or
+//! `FINGERPRINT_ALLOWED_ROOT`) confines requests to the music library:
+//! paths are canonicalized first, so `..` tricks and symlinks pointing out
+//! of the root are caught, and anything resolving outside gets a 403.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use crate::audio::loader::sniff_file;
+use crate::error::{FingerprintError, Result};
+
+/// File extensions accepted by the path-based routes (lowercase)
+pub const AUDIO_EXTENSIONS: &[&str] = &[
+    "wav", "wave", "flac", "mp3", "m4a", "m4b", "mp4", "aac", "adts", "ogg", "oga", "opus",
+    "aiff", "aif", "aifc", "wma", "mka", "mkv", "webm", "weba", "dsf", "dff", "au", "snd",
+    "mov", "alac",
+];
+
+/// Environment variable naming the allowed root when `--allowed-root` isn't given
+pub const ALLOWED_ROOT_ENV: &str = "FINGERPRINT_ALLOWED_ROOT";
+
+static ALLOWED_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Set the allowed root at startup; `cli_root` (from `--allowed-root`) takes
+/// precedence over `ALLOWED_ROOT_ENV`
+///
+/// Fails if the directory can't be canonicalized: silently serving every path
+/// because of a typo in the root would defeat the restriction.
+pub fn init_allowed_root(cli_root: Option<PathBuf>) -> std::io::Result<Option<&'static Path>> {
+    let root = match cli_root.or_else(|| std::env::var_os(ALLOWED_ROOT_ENV).map(PathBuf::from)) {
+        Some(root) => Some(std::fs::canonicalize(root)?),
+        None => None,
+    };
+    Ok(ALLOWED_ROOT.get_or_init(|| root).as_deref())
+}
+
+/// Directory requested files must resolve into, as set by `init_allowed_root`;
+/// `None` allows any path
+pub fn allowed_root() -> Option<&'static Path> {
+    ALLOWED_ROOT.get().and_then(|root| root.as_deref())
+}
+
+/// Check a requested file path and resolve it to a canonical path
+///
+/// Rejects empty paths, paths with `..` components and extensions outside
+/// `AUDIO_EXTENSIONS`, then canonicalizes (resolving symlinks) and, when
+/// `allowed_root` is set, requires the result to lie inside it. A file with
+/// no extension must start with a recognized audio signature.
+///
+/// # Arguments
+/// * `filepath` - Path as sent by the client
+/// * `allowed_root` - Canonical directory the file must resolve into
+///
+/// # Returns
+/// * Canonical path to load, `FingerprintError::InvalidRequest` (400) for a
+///   malformed path, `FingerprintError::Forbidden` (403) for one outside
+///   `allowed_root`, or `FingerprintError::FileNotFound` (404) if it doesn't exist
+pub fn validate_filepath(filepath: &str, allowed_root: Option<&Path>) -> Result<PathBuf> {
+    if filepath.trim().is_empty() {
+        return Err(FingerprintError::InvalidRequest("filepath is empty".to_string()));
+    }
+
+    let path = Path::new(filepath);
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(FingerprintError::InvalidRequest(format!(
+            "filepath must not contain '..' components: {}",
+            filepath
+        )));
+    }
+
+    if path.extension().is_some() {
+        check_extension(path)?;
+    }
+
+    let canonical = std::fs::canonicalize(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => FingerprintError::FileNotFound(filepath.to_string()),
+        _ => FingerprintError::InvalidRequest(format!("Cannot resolve filepath {}: {}", filepath, e)),
+    })?;
+
+    if let Some(root) = allowed_root {
+        if !canonical.starts_with(root) {
+            return Err(FingerprintError::Forbidden(format!(
+                "filepath is outside the allowed root: {}",
+                filepath
+            )));
+        }
+    }
+
+    // A symlink named *.wav may point at anything
+    if canonical.extension().is_some() {
+        check_extension(&canonical)?;
+    } else if sniff_file(&canonical).is_none() {
+        return Err(FingerprintError::InvalidRequest(format!(
+            "No file extension and no recognized audio signature: {}",
+            filepath
+        )));
+    }
+
+    Ok(canonical)
+}
+
+fn check_extension(path: &Path) -> Result<()> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        Ok(())
+    } else {
+        Err(FingerprintError::InvalidRequest(format!(
+            "Unsupported file extension {:?}: {}",
+            ext,
+            path.display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::write_test_wav;
+
+    fn assert_invalid(result: Result<PathBuf>, needle: &str) {
+        match result {
+            Err(FingerprintError::InvalidRequest(msg)) => assert!(msg.contains(needle), "message: {}", msg),
+            other => panic!("expected InvalidRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_empty_traversal_and_non_audio_paths() {
+        assert_invalid(validate_filepath("  ", None), "empty");
+        assert_invalid(validate_filepath("/music/../etc/passwd.wav", None), "'..'");
+        assert_invalid(validate_filepath("/music/notes.TXT", None), "\"txt\"");
+        // Extensionless files must look like audio
+        assert_invalid(validate_filepath("/etc/passwd", None), "signature");
+        assert_invalid(validate_filepath("/etc", None), "signature");
+    }
+
+    #[test]
+    fn allowed_root_admits_inside_and_forbids_outside() {
+        let root = std::env::temp_dir().join(format!("fingerprint-server-{}-root", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let root = std::fs::canonicalize(&root).unwrap();
+        let inside = root.join("track.wav");
+        let outside = write_test_wav("validation-outside");
+        std::fs::copy(&outside, &inside).unwrap();
+
+        let resolved = validate_filepath(inside.to_str().unwrap(), Some(&root)).unwrap();
+        assert_eq!(resolved, inside);
+
+        let forbidden = |path: &Path| match validate_filepath(path.to_str().unwrap(), Some(&root)) {
+            Err(FingerprintError::Forbidden(msg)) => assert!(msg.contains("allowed root"), "message: {}", msg),
+            other => panic!("expected Forbidden for {}, got {:?}", path.display(), other),
+        };
+        forbidden(&outside);
+
+        // A symlink inside the root doesn't grant access to its target
+        #[cfg(unix)]
+        {
+            let link = root.join("escape.wav");
+            std::os::unix::fs::symlink(&outside, &link).unwrap();
+            forbidden(&link);
+        }
+
+        // Without a root any existing audio file is accepted
+        assert!(validate_filepath(outside.to_str().unwrap(), None).is_ok());
+        assert!(matches!(
+            validate_filepath("/no/such/file.flac", None),
+            Err(FingerprintError::FileNotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_file(outside).ok();
+    }
+}
@@ -0,0 +1,219 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::api::fingerprint::fingerprint_track;
+use crate::api::limiter::ConcurrencyLimiter;
+use crate::analysis::analyzer::CancelToken;
+use crate::models::request::{FingerprintRequest, ProgressMessage};
+
+/// `GET /fingerprint/ws`: fingerprint a file with progress updates
+///
+/// The client sends one `FingerprintRequest` as a text frame within
+/// `REQUEST_TIMEOUT` and receives `ProgressMessage`s until the terminal
+/// `done`/`error`, after which the server closes the socket. A concurrency
+/// slot is taken once the request has been parsed and held until the
+/// analysis ends, even if the client disconnects first; when the server is
+/// saturated the request is answered with an `error` message.
+pub async fn fingerprint_ws_handler(
+    State(limiter): State<ConcurrencyLimiter>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, limiter))
+}
+
+/// How long a client may take to send its request after the upgrade
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+async fn handle_socket(mut socket: WebSocket, limiter: ConcurrencyLimiter) {
+    let req = match tokio::time::timeout(REQUEST_TIMEOUT, socket.recv()).await {
+        Ok(Some(Ok(Message::Text(text)))) => serde_json::from_str::<FingerprintRequest>(&text),
+        Ok(Some(Ok(Message::Binary(bytes)))) => serde_json::from_slice::<FingerprintRequest>(&bytes),
+        Ok(_) => return,
+        Err(_) => {
+            let error = format!("No request received within {}s", REQUEST_TIMEOUT.as_secs());
+            let _ = send(&mut socket, &ProgressMessage::Error { error }).await;
+            let _ = socket.close().await;
+            return;
+        }
+    };
+
+    let req = match req {
+        Ok(req) => req,
+        Err(e) => {
+            let error = format!("Failed to parse JSON: {}", e);
+            let _ = send(&mut socket, &ProgressMessage::Error { error }).await;
+            let _ = socket.close().await;
+            return;
+        }
+    };
+
+    let permit = match limiter.try_acquire() {
+        Ok(permit) => permit,
+        Err(e) => {
+            let _ = send(&mut socket, &ProgressMessage::Error { error: e.to_string() }).await;
+            let _ = socket.close().await;
+            return;
+        }
+    };
+
+    if send(&mut socket, &ProgressMessage::Decoding).await.is_err() {
+        return;
+    }
+
+    // The progress callback runs on the blocking analysis thread; forward
+    // through a channel. The sender is dropped with the callback, which ends
+    // the forwarding loop once analysis finishes (or loading fails). The
+    // task owns the permit, so the slot stays taken while it runs.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let cancel = CancelToken::new();
+    let task_cancel = cancel.clone();
+    let task = tokio::spawn(async move {
+        let _permit = permit;
+        fingerprint_track(
+            req,
+            move |percent| {
+                let _ = tx.send(percent);
+            },
+            task_cancel,
+        )
+        .await
+    });
+
+    while let Some(percent) = rx.recv().await {
+        if send(&mut socket, &ProgressMessage::Analyzing { percent }).await.is_err() {
+            // Client went away; stop the analysis at its next checkpoint
+            cancel.cancel();
+            return;
+        }
+    }
+
+    let message = match task.await {
+        Ok(Ok(response)) => ProgressMessage::Done { response: Box::new(response) },
+        Ok(Err(e)) => ProgressMessage::Error { error: e.to_string() },
+        Err(e) => ProgressMessage::Error { error: format!("Task join error: {}", e) },
+    };
+    let _ = send(&mut socket, &message).await;
+    let _ = socket.close().await;
+}
+
+async fn send(socket: &mut WebSocket, message: &ProgressMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("ProgressMessage serializes");
+    socket.send(Message::Text(text)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::limiter::ConcurrencyLimiter;
+    use crate::test_support::{spawn_server, spawn_server_with_limiter, write_test_wav};
+    use std::time::Duration;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[tokio::test]
+    async fn streams_progress_until_done() {
+        let addr = spawn_server().await;
+        let path = write_test_wav("ws-progress");
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/fingerprint/ws", addr))
+            .await
+            .expect("connect");
+        let request = serde_json::json!({ "track_id": 7, "filepath": path.to_str().unwrap() });
+        ws.send(Message::Text(request.to_string())).await.unwrap();
+
+        let mut statuses = Vec::new();
+        let mut last_percent = 0;
+        let mut terminal = None;
+        while let Some(message) = ws.next().await {
+            let text = match message.expect("websocket message") {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let status = value["status"].as_str().unwrap().to_string();
+            if status == "analyzing" {
+                let percent = value["percent"].as_u64().unwrap();
+                assert!(percent >= last_percent, "progress went backwards");
+                last_percent = percent;
+            }
+            if status == "done" || status == "error" {
+                terminal = Some(value);
+            }
+            statuses.push(status);
+        }
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(statuses.first().map(String::as_str), Some("decoding"));
+        assert!(statuses.iter().any(|s| s == "analyzing"));
+        assert_eq!(last_percent, 100);
+        let terminal = terminal.expect("terminal message");
+        assert_eq!(terminal["status"], "done", "unexpected terminal message: {}", terminal);
+        assert_eq!(terminal["track_id"], 7);
+        assert!(terminal["fingerprint"]["tempo_bpm"].is_number());
+    }
+
+    #[tokio::test]
+    async fn reports_missing_file_as_error() {
+        let addr = spawn_server().await;
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/fingerprint/ws", addr))
+            .await
+            .expect("connect");
+        let request = serde_json::json!({ "track_id": 1, "filepath": "/nonexistent/track.wav" });
+        ws.send(Message::Text(request.to_string())).await.unwrap();
+
+        let mut last = None;
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            last = Some(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+        }
+        let last = last.expect("at least one message");
+        assert_eq!(last["status"], "error");
+    }
+
+    #[tokio::test]
+    async fn silent_client_holds_no_slot() {
+        let limiter = ConcurrencyLimiter::new(1);
+        let addr = spawn_server_with_limiter(limiter.clone()).await;
+        let (_ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/fingerprint/ws", addr))
+            .await
+            .expect("connect");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn disconnect_keeps_slot_until_analysis_ends() {
+        let limiter = ConcurrencyLimiter::new(1);
+        let addr = spawn_server_with_limiter(limiter.clone()).await;
+        let path = write_test_wav("ws-disconnect");
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/fingerprint/ws", addr))
+            .await
+            .expect("connect");
+        let request = serde_json::json!({ "track_id": 3, "filepath": path.to_str().unwrap() });
+        ws.send(Message::Text(request.to_string())).await.unwrap();
+
+        // Leave as soon as the analysis has started
+        while let Some(Ok(message)) = ws.next().await {
+            if let Message::Text(text) = message {
+                if text.contains("analyzing") {
+                    break;
+                }
+            }
+        }
+        drop(ws);
+        assert_eq!(limiter.in_flight(), 1, "slot released while the analysis runs");
+
+        // The disconnect cancels the analysis, which then frees the slot
+        let released = tokio::time::timeout(Duration::from_secs(60), async {
+            while limiter.in_flight() > 0 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        let _ = std::fs::remove_file(&path);
+        assert!(released.is_ok(), "slot never released");
+    }
+}
@@ -1,12 +1,14 @@
 use crate::error::{FingerprintError, Result};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
-use symphonia::core::audio::{AudioBufferRef, Signal};
-use symphonia::core::codecs::DecoderOptions;
+use std::io::Read;
+use symphonia::core::audio::{AudioBufferRef, Channels, Layout, Signal};
+use symphonia::core::codecs::{CodecParameters, DecoderOptions, CODEC_TYPE_AAC};
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::probe::Hint;
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 #[derive(Debug, Clone)]
 pub struct AudioData {
@@ -15,8 +17,164 @@ pub struct AudioData {
     pub samples: Arc<Vec<f64>>,
     pub sample_rate: u32,
     pub channels: u16,
+    pub codec: CodecInfo,
+    pub decode: DecodeStats,
 }
 
+/// What the decoder reported about the encoded stream
+///
+/// The extension says little about the content: an `.m4a` can hold lossy
+/// AAC or lossless ALAC, a `.wav` 16-bit integer or 32-bit float PCM. Fields
+/// are `None` when the container doesn't say.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodecInfo {
+    /// Codec short name as Symphonia reports it ("flac", "alac", "aac",
+    /// "mp3", "pcm_s16le", ...)
+    pub codec: Option<String>,
+    /// Bits per decoded sample (lossless and PCM codecs)
+    pub bits_per_sample: Option<u32>,
+    /// "mono", "stereo", "2.1", "5.1", or the channel list for other layouts
+    pub channel_layout: Option<String>,
+    /// Whether the codec is lossless; `None` for codecs not classified here
+    pub lossless: Option<bool>,
+}
+
+impl CodecInfo {
+    fn from_params(params: &CodecParameters) -> Self {
+        let codec = symphonia::default::get_codecs()
+            .get_codec(params.codec)
+            .map(|descriptor| descriptor.short_name.to_string());
+        Self {
+            lossless: codec.as_deref().and_then(is_lossless_codec),
+            codec,
+            bits_per_sample: params.bits_per_sample,
+            channel_layout: track_channels(params).map(layout_name),
+        }
+    }
+}
+
+/// Packet accounting of a decode
+///
+/// Packets the decoder rejects are skipped rather than failing the file, as
+/// long as at least `min_decoded_fraction()` of them decode; the result is
+/// then marked `partial`. Decoders that stop at the first error (Claxon)
+/// always report full decodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecodeStats {
+    /// Some packets were undecodable and are missing from the signal
+    #[serde(default)]
+    pub partial: bool,
+    /// Number of packets skipped
+    #[serde(default)]
+    pub skipped_packets: usize,
+}
+
+/// Default for `min_decoded_fraction()`
+pub const DEFAULT_MIN_DECODED_FRACTION: f64 = 0.9;
+
+/// Environment variable overriding `DEFAULT_MIN_DECODED_FRACTION`
+pub const MIN_DECODED_FRACTION_ENV: &str = "FINGERPRINT_MIN_DECODED_FRACTION";
+
+/// Consecutive container errors after which a stream is considered
+/// unreadable rather than locally damaged
+const MAX_CONSECUTIVE_PACKET_ERRORS: usize = 64;
+
+static MIN_DECODED_FRACTION: OnceLock<f64> = OnceLock::new();
+
+/// Share of packets that must decode for a damaged file to be accepted as a
+/// partial decode, from `MIN_DECODED_FRACTION_ENV` (read once) or
+/// `DEFAULT_MIN_DECODED_FRACTION`
+pub fn min_decoded_fraction() -> f64 {
+    *MIN_DECODED_FRACTION.get_or_init(|| {
+        crate::config::fraction_from_env(MIN_DECODED_FRACTION_ENV, DEFAULT_MIN_DECODED_FRACTION)
+    })
+}
+
+/// Classify a Symphonia codec short name as lossless or lossy
+fn is_lossless_codec(codec: &str) -> Option<bool> {
+    match codec {
+        "flac" | "alac" | "wavpack" => Some(true),
+        c if c.starts_with("pcm_") => Some(true),
+        "mp1" | "mp2" | "mp3" | "aac" | "vorbis" | "opus" => Some(false),
+        c if c.starts_with("adpcm_") => Some(false),
+        _ => None,
+    }
+}
+
+fn layout_name(channels: Channels) -> String {
+    let named = [
+        (Layout::Mono, "mono"),
+        (Layout::Stereo, "stereo"),
+        (Layout::TwoPointOne, "2.1"),
+        (Layout::FivePointOne, "5.1"),
+    ];
+    named
+        .iter()
+        .find(|(layout, _)| layout.into_channels() == channels)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("{:?}", channels))
+}
+
+/// Receiver of decoded mono audio, one block at a time
+///
+/// The decoders push each packet's samples as soon as they are decoded, so
+/// a sink that doesn't keep them (see `stream_audio`) analyses a file in
+/// memory bounded by the packet size rather than the track length.
+pub trait SampleSink {
+    /// Called once the stream format is known, before any `push`
+    ///
+    /// Called again if a decoder fails part-way and another decoder restarts
+    /// the file (FLAC falls back from Claxon to Symphonia); anything pushed
+    /// before must then be discarded.
+    fn start(&mut self, sample_rate: u32, channels: u16) -> Result<()>;
+
+    /// Consume the next block of mono samples (all finite)
+    fn push(&mut self, block: &[f64]) -> Result<()>;
+}
+
+/// Sink that keeps everything, for the batch `load_audio` path
+#[derive(Default)]
+struct CollectSink {
+    samples: Vec<f64>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl SampleSink for CollectSink {
+    fn start(&mut self, sample_rate: u32, channels: u16) -> Result<()> {
+        self.samples.clear();
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        Ok(())
+    }
+
+    fn push(&mut self, block: &[f64]) -> Result<()> {
+        self.samples.extend_from_slice(block);
+        Ok(())
+    }
+}
+
+impl CollectSink {
+    fn into_audio(self, filepath: &str, (codec, decode): (CodecInfo, DecodeStats)) -> AudioData {
+        tracing::debug!(
+            "Loaded {} samples at {} Hz from {}",
+            self.samples.len(),
+            self.sample_rate,
+            filepath
+        );
+        AudioData {
+            samples: Arc::new(self.samples),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            codec,
+            decode,
+        }
+    }
+}
+
+/// Frames per block pushed by the Claxon decoder (Symphonia pushes packets)
+const CLAXON_BLOCK_FRAMES: usize = 4096;
+
 /// Load audio from file path and return samples + metadata
 ///
 /// Supports: WAV, FLAC, MP3, AAC, OGG, M4A, WMA
@@ -34,26 +192,99 @@ pub async fn load_audio(filepath: &str) -> Result<AudioData> {
         .map_err(|e| FingerprintError::InternalError(format!("Task join error: {}", e)))?
 }
 
+/// Decode an in-memory audio file (e.g. an uploaded request body)
+///
+/// `format_hint` is a format name or file extension ("wav", "flac", "mp3",
+/// ...); without it Symphonia probes the container from the bytes alone.
+/// Always returns mono, like `load_audio`.
+pub async fn load_audio_bytes(bytes: Vec<u8>, format_hint: Option<String>) -> Result<AudioData> {
+    if bytes.is_empty() {
+        return Err(FingerprintError::InvalidAudio("Empty audio body".to_string()));
+    }
+
+    tokio::task::spawn_blocking(move || load_audio_bytes_sync(bytes, format_hint.as_deref()))
+        .await
+        .map_err(|e| FingerprintError::InternalError(format!("Task join error: {}", e)))?
+}
+
+/// Decode the file at `filepath` into `sink` block by block (blocking)
+///
+/// Same format detection and decoder preference as `load_audio`, but the
+/// decoded signal is never held in full: memory use is one packet plus
+/// whatever the sink keeps. Call from a blocking thread.
+///
+/// # Returns
+/// What the decoder reported about the stream's codec, and how many packets
+/// it skipped
+pub fn stream_audio<S: SampleSink>(filepath: &str, sink: &mut S) -> Result<(CodecInfo, DecodeStats)> {
+    if !std::path::Path::new(filepath).exists() {
+        return Err(FingerprintError::FileNotFound(filepath.to_string()));
+    }
+    decode_file(filepath, sink)
+}
+
+fn load_audio_bytes_sync(bytes: Vec<u8>, format_hint: Option<&str>) -> Result<AudioData> {
+    tracing::debug!(
+        "Loading {} bytes of in-memory audio (format hint: {})",
+        bytes.len(),
+        format_hint.unwrap_or("none")
+    );
+    let detected_format = resolve_format(format_hint.map(normalize_format_name), &bytes, "request body");
+
+    // Same decoder preference as files: Claxon first for FLAC
+    let mut sink = CollectSink::default();
+    let mut claxon_failed = false;
+    if detected_format.as_deref() == Some("flac") {
+        match decode_flac_with_claxon(std::io::Cursor::new(&bytes), "request body", &mut sink) {
+            Ok(codec) => return Ok(sink.into_audio("request body", (codec, DecodeStats::default()))),
+            Err(e) => {
+                claxon_failed = true;
+                tracing::warn!("Claxon decoder failed for FLAC request body, falling back to Symphonia: {}", e);
+            }
+        }
+    }
+
+    let hint = probe_hint(detected_format.as_deref());
+    let mss = MediaSourceStream::new(Box::new(std::io::Cursor::new(bytes)), Default::default());
+    let decoded = decode_with_symphonia(
+        mss,
+        &hint,
+        detected_format.as_deref().unwrap_or("unknown"),
+        "request body",
+        claxon_failed,
+        &mut sink,
+    )?;
+    Ok(sink.into_audio("request body", decoded))
+}
+
 fn load_audio_sync(filepath: &str) -> Result<AudioData> {
+    let mut sink = CollectSink::default();
+    let decoded = decode_file(filepath, &mut sink)?;
+    Ok(sink.into_audio(filepath, decoded))
+}
+
+/// Decode a file into `sink`, picking the decoder from the extension
+///
+/// A file without an extension is handed to Symphonia unhinted, which
+/// detects the container from its content.
+fn decode_file<S: SampleSink>(filepath: &str, sink: &mut S) -> Result<(CodecInfo, DecodeStats)> {
     tracing::debug!("Loading audio from: {}", filepath);
 
-    // Detect format from file extension FIRST
-    let detected_format = detect_format_from_extension(filepath)?;
-    tracing::info!("Detected format from extension '{}': {}",
-        std::path::Path::new(filepath)
-            .extension()
-            .map(|e| e.to_string_lossy().into_owned())
-            .unwrap_or_else(|| "no extension".to_string()),
-        detected_format
-    );
+    // Detect format from file extension FIRST, checked against the content
+    let head = read_head(filepath)?;
+    let detected_format = resolve_format(detect_format_from_extension(filepath), &head, filepath)
+        .unwrap_or_else(|| "unknown".to_string());
 
     // Try Claxon for FLAC files first (faster, handles most variants)
     // Fall back to Symphonia if Claxon fails (for edge cases/corrupted FLACs)
     let mut claxon_failed = false;
     if detected_format == "flac" {
         tracing::info!("Attempting Claxon decoder for FLAC file: {}", filepath);
-        match load_flac_with_claxon(filepath) {
-            Ok(audio) => return Ok(audio),
+        let flac = File::open(filepath)
+            .map_err(|e| FingerprintError::FileNotFound(format!("{}: {}", filepath, e)))
+            .and_then(|file| decode_flac_with_claxon(std::io::BufReader::new(file), filepath, sink));
+        match flac {
+            Ok(codec) => return Ok((codec, DecodeStats::default())),
             Err(e) => {
                 claxon_failed = true;
                 tracing::warn!("Claxon decoder failed for FLAC: {}, falling back to Symphonia: {}", filepath, e);
@@ -66,9 +297,7 @@ fn load_audio_sync(filepath: &str) -> Result<AudioData> {
     tracing::info!("Using Symphonia decoder for {} format", detected_format);
 
     // Create hint with detected format to guide Symphonia's probe
-    let mut hint = Hint::new();
-    hint.with_extension(&detected_format);
-    tracing::debug!("Using format hint: {}", detected_format);
+    let hint = probe_hint(Some(&detected_format));
 
     // Open file for probing
     let file = File::open(filepath)
@@ -78,11 +307,96 @@ fn load_audio_sync(filepath: &str) -> Result<AudioData> {
     let source = ReadOnlySource::new(file);
     let mss = MediaSourceStream::new(Box::new(source), Default::default());
 
+    decode_with_symphonia(mss, &hint, &detected_format, filepath, claxon_failed, sink)
+}
+
+/// Stream parameters read from a file's headers by `probe_audio`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeInfo {
+    /// From the container's frame count; `None` when the container doesn't
+    /// record it (e.g. some MP3s without a Xing/Info header)
+    pub duration_sec: Option<f64>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    #[serde(flatten)]
+    pub codec: CodecInfo,
+}
+
+/// Read duration, sample rate, channels and codec without decoding audio
+///
+/// Symphonia parses the container headers only, so the cost doesn't grow
+/// with the length of the file. Picks the format hint as `load_audio`
+/// does: the extension, unless the content names another container.
+pub async fn probe_audio(filepath: &str) -> Result<ProbeInfo> {
+    if !std::path::Path::new(filepath).exists() {
+        return Err(FingerprintError::FileNotFound(filepath.to_string()));
+    }
+
+    let filepath = filepath.to_string();
+    tokio::task::spawn_blocking(move || probe_audio_sync(&filepath))
+        .await
+        .map_err(|e| FingerprintError::InternalError(format!("Task join error: {}", e)))?
+}
+
+fn probe_audio_sync(filepath: &str) -> Result<ProbeInfo> {
+    let head = read_head(filepath)?;
+    let detected_format = resolve_format(detect_format_from_extension(filepath), &head, filepath)
+        .unwrap_or_else(|| "unknown".to_string());
+    let hint = probe_hint(Some(&detected_format));
+
+    let file = File::open(filepath)
+        .map_err(|e| FingerprintError::FileNotFound(format!("{}: {}", filepath, e)))?;
+    let mss = MediaSourceStream::new(
+        Box::new(symphonia::core::io::ReadOnlySource::new(file)),
+        Default::default(),
+    );
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &Default::default())
+        .map_err(|e| {
+            FingerprintError::UnsupportedFormat(format!("Failed to probe format '{}': {}", detected_format, e))
+        })?;
+
+    let params = probed
+        .format
+        .tracks()
+        .iter()
+        .map(|t| &t.codec_params)
+        .find(|p| p.sample_rate.is_some())
+        .ok_or_else(|| FingerprintError::InvalidAudio("No audio tracks found".to_string()))?;
+    let sample_rate = params
+        .sample_rate
+        .ok_or_else(|| FingerprintError::InvalidAudio("No sample rate found".to_string()))?;
+    let channels = track_channels(params)
+        .ok_or_else(|| FingerprintError::InvalidAudio("No channel info found".to_string()))?
+        .count() as u16;
+
+    Ok(ProbeInfo {
+        duration_sec: params.n_frames.map(|n| n as f64 / sample_rate as f64),
+        sample_rate,
+        channels,
+        codec: CodecInfo::from_params(params),
+    })
+}
+
+/// Probe and decode a media stream with Symphonia, averaging to mono
+///
+/// `filepath` only labels log and error messages (a path, or
+/// "request body" for uploads). Undecodable packets are skipped and
+/// counted; fails with `InvalidAudio` if fewer than
+/// `min_decoded_fraction()` of the packets decode.
+fn decode_with_symphonia<S: SampleSink>(
+    mss: MediaSourceStream,
+    hint: &Hint,
+    detected_format: &str,
+    filepath: &str,
+    claxon_failed: bool,
+    sink: &mut S,
+) -> Result<(CodecInfo, DecodeStats)> {
     // Probe format with detected format hint
     // Note: Symphonia's probe will try formats internally, but the hint guides prioritization
     let probed = symphonia::default::get_probe()
         .format(
-            &hint,
+            hint,
             mss,
             &FormatOptions::default(),
             &Default::default(),
@@ -129,20 +443,26 @@ fn load_audio_sync(filepath: &str) -> Result<AudioData> {
         .codec_params
         .sample_rate
         .ok_or_else(|| FingerprintError::InvalidAudio("No sample rate found".to_string()))?;
-    let channels = track
-        .codec_params
-        .channels
+    let channels = track_channels(&track.codec_params)
         .ok_or_else(|| FingerprintError::InvalidAudio("No channel info found".to_string()))?
         .count() as u16;
 
+    let codec = CodecInfo::from_params(&track.codec_params);
     tracing::debug!(
-        "Audio format: {} Hz, {} channels",
+        "Audio format: {} Hz, {} channels, codec {}",
         sample_rate,
-        channels
+        channels,
+        codec.codec.as_deref().unwrap_or("unknown")
     );
 
-    // Decode all samples
-    let mut samples = Vec::new();
+    sink.start(sample_rate, channels)?;
+
+    // Decode packet by packet, handing each one to the sink
+    let mut block = Vec::new();
+    let mut total_samples = 0usize;
+    let mut decoded_packets = 0usize;
+    let mut skipped_packets = 0usize;
+    let mut consecutive_errors = 0usize;
     let mut decoder = symphonia::default::get_codecs()
         .make(
             &track.codec_params,
@@ -153,48 +473,80 @@ fn load_audio_sync(filepath: &str) -> Result<AudioData> {
     loop {
         match format.next_packet() {
             Ok(packet) => {
+                consecutive_errors = 0;
                 match decoder.decode(&packet) {
                     Ok(buf) => {
-                        collect_samples(&mut samples, &buf, channels as usize)?;
+                        decoded_packets += 1;
+                        block.clear();
+                        collect_samples(&mut block, &buf, channels as usize)?;
+                        check_finite(&block, "Audio")?;
+                        total_samples += block.len();
+                        if !block.is_empty() {
+                            sink.push(&block)?;
+                        }
                     }
-                    Err(symphonia::core::errors::Error::DecodeError(_)) => {
+                    Err(symphonia::core::errors::Error::DecodeError(e)) => {
                         // Skip frames that can't be decoded
-                        continue;
+                        tracing::debug!("Skipping undecodable packet in {}: {}", filepath, e);
+                        skipped_packets += 1;
                     }
                     Err(e) => return Err(FingerprintError::DecodingError(format!("{}", e))),
                 }
             }
             Err(symphonia::core::errors::Error::IoError(_)) => break,
             Err(symphonia::core::errors::Error::Unsupported(_)) => break,
+            Err(symphonia::core::errors::Error::DecodeError(e))
+                if consecutive_errors < MAX_CONSECUTIVE_PACKET_ERRORS =>
+            {
+                // Damaged container framing; the reader resyncs on the next call
+                tracing::debug!("Skipping unreadable packet in {}: {}", filepath, e);
+                skipped_packets += 1;
+                consecutive_errors += 1;
+            }
             Err(e) => return Err(FingerprintError::DecodingError(format!("{}", e))),
         }
     }
 
     // Validate audio
-    if samples.is_empty() {
+    if total_samples == 0 {
         return Err(FingerprintError::InvalidAudio(
             "No audio samples decoded".to_string(),
         ));
     }
 
-    if !samples.iter().all(|s| s.is_finite()) {
-        return Err(FingerprintError::InvalidAudio(
-            "Audio contains NaN or infinite values".to_string(),
-        ));
+    let stats = DecodeStats { partial: skipped_packets > 0, skipped_packets };
+    if stats.partial {
+        let total_packets = decoded_packets + skipped_packets;
+        let min_fraction = min_decoded_fraction();
+        if (decoded_packets as f64) < min_fraction * total_packets as f64 {
+            return Err(FingerprintError::InvalidAudio(format!(
+                "File appears corrupted: only {} of {} packets decodable (need {:.0}%)",
+                decoded_packets,
+                total_packets,
+                min_fraction * 100.0
+            )));
+        }
+        tracing::warn!(
+            "Partially decoded {}: skipped {} of {} packets",
+            filepath,
+            skipped_packets,
+            total_packets
+        );
     }
 
-    tracing::debug!(
-        "Loaded {} samples at {} Hz from {}",
-        samples.len(),
-        sample_rate,
-        filepath
-    );
+    Ok((codec, stats))
+}
 
-    Ok(AudioData {
-        samples: Arc::new(samples),
-        sample_rate,
-        channels,
-    })
+/// Reject a decoded block containing NaN or infinite samples
+fn check_finite(block: &[f64], label: &str) -> Result<()> {
+    if block.iter().all(|s| s.is_finite()) {
+        Ok(())
+    } else {
+        Err(FingerprintError::InvalidAudio(format!(
+            "{} contains NaN or infinite values",
+            label
+        )))
+    }
 }
 
 fn collect_samples(
@@ -261,26 +613,19 @@ fn collect_samples(
     Ok(())
 }
 
-/// Load FLAC audio using pure Rust Claxon decoder
+/// Decode FLAC audio using pure Rust Claxon decoder
 ///
 /// Claxon handles all FLAC variants including non-standard metadata,
 /// custom compression, and edge cases that Symphonia struggles with.
+/// `filepath` only labels log messages.
 ///
-/// Returns: AudioData with mono samples normalized to [-1.0, +1.0]
-fn load_flac_with_claxon(filepath: &str) -> Result<AudioData> {
-    use std::fs::File;
-    use std::io::BufReader;
-
+/// Pushes mono samples normalized to [-1.0, +1.0] to `sink` in blocks of
+/// `CLAXON_BLOCK_FRAMES`.
+fn decode_flac_with_claxon<R: std::io::Read, S: SampleSink>(input: R, filepath: &str, sink: &mut S) -> Result<CodecInfo> {
     tracing::debug!("Loading FLAC with Claxon: {}", filepath);
 
-    // Open file
-    let file = File::open(filepath)
-        .map_err(|e| FingerprintError::FileNotFound(format!("{}: {}", filepath, e)))?;
-
-    let buffered = BufReader::new(file);
-
     // Create FLAC reader
-    let mut reader = claxon::FlacReader::new(buffered)
+    let mut reader = claxon::FlacReader::new(input)
         .map_err(|e| FingerprintError::DecodingError(format!("FLAC reader error: {}", e)))?;
 
     // Extract metadata
@@ -300,83 +645,245 @@ fn load_flac_with_claxon(filepath: &str) -> Result<AudioData> {
         ));
     }
 
-    // Decode all samples using Claxon's sample iterator
-    // Claxon returns raw i32 samples in interleaved format (L, R, L, R, ... for stereo)
-    let mut raw_samples: Vec<f64> = Vec::new();
+    let codec = CodecInfo {
+        codec: Some("flac".to_string()),
+        bits_per_sample: Some(streaminfo.bits_per_sample),
+        channel_layout: flac_channel_layout(channels).map(layout_name),
+        lossless: Some(true),
+    };
+
+    sink.start(sample_rate, channels)?;
+
+    // Claxon returns raw i32 samples in interleaved format (L, R, L, R, ...
+    // for stereo); average each complete frame to mono as it arrives. A
+    // trailing partial frame is dropped.
+    let num_channels = channels as usize;
+    let mut block = Vec::with_capacity(CLAXON_BLOCK_FRAMES);
+    let mut total_samples = 0usize;
+    let mut frame_sum = 0.0f64;
+    let mut channel = 0;
 
     for sample_result in reader.samples() {
         let sample_i32 = sample_result
             .map_err(|e| FingerprintError::DecodingError(format!("FLAC sample decode error: {}", e)))?;
 
         // Normalize i32 to f64 [-1.0, +1.0] range
-        let normalized = sample_i32 as f64 / i32::MAX as f64;
-        raw_samples.push(normalized);
+        frame_sum += sample_i32 as f64 / i32::MAX as f64;
+        channel += 1;
+        if channel == num_channels {
+            block.push(frame_sum / num_channels as f64);
+            frame_sum = 0.0;
+            channel = 0;
+
+            if block.len() == CLAXON_BLOCK_FRAMES {
+                check_finite(&block, "FLAC audio")?;
+                total_samples += block.len();
+                sink.push(&block)?;
+                block.clear();
+            }
+        }
+    }
+
+    if !block.is_empty() {
+        check_finite(&block, "FLAC audio")?;
+        total_samples += block.len();
+        sink.push(&block)?;
     }
 
-    if raw_samples.is_empty() {
+    // Validate audio
+    if total_samples == 0 {
         return Err(FingerprintError::InvalidAudio(
             "No audio samples decoded from FLAC file".to_string(),
         ));
     }
 
-    // De-interleave channels and average to mono
-    // Assume samples are in interleaved format: [L, R, L, R, ...] for stereo
-    let num_channels = channels as usize;
-    let num_frames = raw_samples.len() / num_channels;
-
-    let mut samples = Vec::with_capacity(num_frames);
-    for frame_idx in 0..num_frames {
-        let mut sum = 0.0f64;
-        for ch in 0..num_channels {
-            let sample_idx = frame_idx * num_channels + ch;
-            if sample_idx < raw_samples.len() {
-                sum += raw_samples[sample_idx];
-            }
-        }
-        let avg = sum / num_channels as f64;
-        samples.push(avg);
+    Ok(codec)
+}
+
+/// A track's channels, from the container or else the codec configuration
+fn track_channels(params: &CodecParameters) -> Option<Channels> {
+    params
+        .channels
+        .or_else(|| params.channel_layout.map(Layout::into_channels))
+        .or_else(|| {
+            let config = params.extra_data.as_deref().filter(|_| params.codec == CODEC_TYPE_AAC)?;
+            aac_config_channels(config)
+        })
+}
+
+/// Channels from an AAC AudioSpecificConfig (ISO 14496-3, 1.6.2.1)
+///
+/// MP4 files leave the channel count of AAC to this codec configuration
+/// rather than the sample entry, and Symphonia doesn't copy it into the
+/// track parameters. Only mono and stereo are mapped, as Symphonia's AAC
+/// decoder supports no more.
+fn aac_config_channels(config: &[u8]) -> Option<Channels> {
+    let mut padded = [0u8; 8];
+    let len = config.len().min(8);
+    padded[..len].copy_from_slice(&config[..len]);
+    let bits = u64::from_be_bytes(padded);
+    let field = |pos: usize, width: usize| (bits >> (64 - pos - width)) & ((1 << width) - 1);
+
+    // 5-bit object type (31 escapes to 6 more bits), then 4-bit frequency
+    // index (15 escapes to a 24-bit rate), then the channel configuration
+    let mut pos = if field(0, 5) == 31 { 11 } else { 5 };
+    pos += if field(pos, 4) == 15 { 28 } else { 4 };
+    if pos + 4 > len * 8 {
+        return None;
     }
+    match field(pos, 4) {
+        1 => Some(Layout::Mono.into_channels()),
+        2 => Some(Layout::Stereo.into_channels()),
+        _ => None,
+    }
+}
 
-    // Validate audio
-    if samples.is_empty() {
-        return Err(FingerprintError::InvalidAudio(
-            "No audio samples decoded from FLAC file".to_string(),
-        ));
+/// Channel mask the FLAC format defines for an independent-channel count
+fn flac_channel_layout(channels: u16) -> Option<Channels> {
+    let front = Channels::FRONT_LEFT | Channels::FRONT_RIGHT;
+    let surround = front | Channels::FRONT_CENTRE | Channels::LFE1;
+    match channels {
+        1 => Some(Layout::Mono.into_channels()),
+        2 => Some(front),
+        3 => Some(front | Channels::FRONT_CENTRE),
+        4 => Some(front | Channels::REAR_LEFT | Channels::REAR_RIGHT),
+        5 => Some(front | Channels::FRONT_CENTRE | Channels::REAR_LEFT | Channels::REAR_RIGHT),
+        6 => Some(surround | Channels::REAR_LEFT | Channels::REAR_RIGHT),
+        _ => None,
     }
+}
 
-    if !samples.iter().all(|s| s.is_finite()) {
-        return Err(FingerprintError::InvalidAudio(
-            "FLAC audio contains NaN or infinite values".to_string(),
-        ));
+/// Bytes read from the start of a file for `sniff_format`
+const SNIFF_BYTES: usize = 16;
+
+/// The first `SNIFF_BYTES` of a file (fewer if it is shorter)
+fn read_head(filepath: &str) -> Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    File::open(filepath)
+        .and_then(|file| file.take(SNIFF_BYTES as u64).read_to_end(&mut head))
+        .map_err(|e| FingerprintError::FileNotFound(format!("{}: {}", filepath, e)))?;
+    Ok(head)
+}
+
+/// Container format of the file at `path` by magic number (`sniff_format`);
+/// `None` if it is unrecognized or can't be read
+pub(crate) fn sniff_file(path: &std::path::Path) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path).and_then(|file| file.take(SNIFF_BYTES as u64).read_to_end(&mut head)).ok()?;
+    sniff_format(&head)
+}
+
+/// Format name reported for the file at `path`: its normalized extension, or
+/// for a file without one the sniffed container ("unknown" if neither)
+pub(crate) fn file_format(path: &std::path::Path) -> String {
+    match path.extension() {
+        Some(ext) => normalize_format_name(&ext.to_string_lossy()),
+        None => sniff_file(path).unwrap_or("unknown").to_string(),
     }
+}
 
-    tracing::debug!(
-        "Loaded {} samples at {} Hz from FLAC file",
-        samples.len(),
-        sample_rate
-    );
+/// Container format from the first bytes of a file, by magic number
+///
+/// Covers the containers Symphonia or Claxon can read. `None` if nothing
+/// matches, which doesn't mean the file is unreadable (e.g. MP3 behind
+/// leading junk).
+fn sniff_format(head: &[u8]) -> Option<&'static str> {
+    match head {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("wav"),
+        [b'f', b'L', b'a', b'C', ..] => Some("flac"),
+        [b'O', b'g', b'g', b'S', ..] => Some("ogg"),
+        [b'F', b'O', b'R', b'M', ..] => Some("aiff"),
+        [b'c', b'a', b'f', b'f', ..] => Some("caf"),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("mkv"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("m4a"),
+        [b'I', b'D', b'3', ..] => Some("mp3"),
+        // 12-bit sync, layer 0: ADTS
+        [0xFF, b, ..] if b & 0xF6 == 0xF0 => Some("aac"),
+        // 11-bit sync, layer I-III: MPEG audio
+        [0xFF, b, ..] if b & 0xE0 == 0xE0 && b & 0x06 != 0 => Some("mp3"),
+        _ => None,
+    }
+}
 
-    Ok(AudioData {
-        samples: Arc::new(samples),
-        sample_rate,
-        channels,
-    })
+/// The `sniff_format` name of the container a format name is stored in
+fn container_family(format: &str) -> &str {
+    match format {
+        "mov" | "alac" => "m4a",
+        "webm" => "mkv",
+        other => other,
+    }
+}
+
+/// Format to decode `label` as: the extension's (or upload hint's) format
+/// unless the content says otherwise
+///
+/// Files are often mislabeled (an MP3 saved as `.wav`) or unlabeled. The
+/// extension picks the decoder (Claxon for FLAC) and the probe hint, so
+/// when the magic number names a different container, the content wins.
+/// Logs which of the two the decision came from.
+fn resolve_format(from_extension: Option<String>, head: &[u8], label: &str) -> Option<String> {
+    match (from_extension, sniff_format(head)) {
+        (Some(extension), Some(content)) if container_family(&extension) != content => {
+            tracing::warn!(
+                "{} is labeled {} but its content is {}; decoding as {}",
+                label,
+                extension,
+                content,
+                content
+            );
+            Some(content.to_string())
+        }
+        (Some(extension), _) => {
+            tracing::info!("Detected format from extension: {}", extension);
+            Some(extension)
+        }
+        (None, Some(content)) => {
+            tracing::info!("No extension on {}, detected {} from content", label, content);
+            Some(content.to_string())
+        }
+        (None, None) => {
+            tracing::info!("No extension on {} and no known signature; letting Symphonia probe", label);
+            None
+        }
+    }
 }
 
 /// Detect audio format from file extension
 ///
 /// Uses file extension to determine the audio format before passing to Symphonia.
-/// This ensures the correct format decoder is used and prevents format detection errors.
+/// `None` when the file has no extension.
 ///
 /// Supports: WAV, FLAC, MP3, AAC, OGG, M4A, AIFF, WMA, MKV, WebM, DSD, OPUS, AU, MOV, etc.
-fn detect_format_from_extension(filepath: &str) -> Result<String> {
-    let ext = std::path::Path::new(filepath)
-        .extension()
-        .ok_or_else(|| FingerprintError::InvalidAudio(
-            format!("File has no extension: {}", filepath)
-        ))?
-        .to_string_lossy()
-        .to_lowercase();
+pub(crate) fn detect_format_from_extension(filepath: &str) -> Option<String> {
+    let ext = std::path::Path::new(filepath).extension()?.to_string_lossy().to_lowercase();
+    Some(normalize_format_name(&ext))
+}
+
+/// Symphonia probe hint for a format name from `normalize_format_name`
+///
+/// Hints must be extensions a Symphonia reader registers, and this server's
+/// format names only partly coincide with them. Checked against Symphonia
+/// 0.5: the ADTS reader registers "aac" (raw AAC frames, as in `.aac`
+/// files), the ISO/MP4 reader "m4a" and "mov" (AAC or ALAC in an MP4
+/// container). Formats with no Symphonia reader (WMA, DSD, AU, ...) get an
+/// empty hint. Symphonia 0.5 picks the reader from the stream's content in
+/// any case, so an unhinted or mislabeled file is still detected.
+pub(crate) fn probe_hint(format: Option<&str>) -> Hint {
+    let extension = match format {
+        Some(f @ ("wav" | "flac" | "mp3" | "aac" | "ogg" | "aiff" | "mkv" | "webm" | "caf" | "mov")) => Some(f),
+        Some("m4a" | "alac") => Some("m4a"),
+        _ => None,
+    };
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    hint
+}
+
+/// Map a file extension or format name to the format name Symphonia expects
+pub fn normalize_format_name(ext: &str) -> String {
+    let ext = ext.trim().trim_start_matches('.').to_lowercase();
 
     // Normalize common extension variations to standard format names
     // These are the exact format names Symphonia expects
@@ -435,5 +942,132 @@ fn detect_format_from_extension(filepath: &str) -> Result<String> {
         unknown => unknown,
     };
 
-    Ok(normalized.to_string())
+    normalized.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        test_adts_bytes, test_flac_bytes, test_flac_bytes_with, test_mp3_bytes, test_mp4_aac_bytes, test_wav_bytes,
+        FlacOptions, AAC_FRAME_SAMPLES,
+    };
+
+    #[tokio::test]
+    async fn flac_reports_codec_and_bit_depth() {
+        let path = std::env::temp_dir().join(format!("fingerprint-server-{}-codec.flac", std::process::id()));
+        std::fs::write(&path, test_flac_bytes(22_050, 2.0)).unwrap();
+        let audio = load_audio(path.to_str().unwrap()).await;
+        std::fs::remove_file(&path).ok();
+        let audio = audio.unwrap();
+
+        assert_eq!((audio.sample_rate, audio.channels, audio.samples.len()), (22_050, 1, 44_100));
+        assert_eq!(audio.codec.codec.as_deref(), Some("flac"));
+        assert_eq!(audio.codec.bits_per_sample, Some(16));
+        assert_eq!(audio.codec.channel_layout.as_deref(), Some("mono"));
+        assert_eq!(audio.codec.lossless, Some(true));
+
+        // Symphonia reports the same for the FLAC stream (the fallback path)
+        let mut sink = CollectSink::default();
+        let bytes = test_flac_bytes(22_050, 2.0);
+        let mss = MediaSourceStream::new(Box::new(std::io::Cursor::new(bytes)), Default::default());
+        let hint = probe_hint(Some("flac"));
+        let (codec, stats) = decode_with_symphonia(mss, &hint, "flac", "test", false, &mut sink).unwrap();
+        assert_eq!(codec, audio.codec);
+        assert_eq!(stats, DecodeStats::default());
+        assert_eq!(sink.samples.len(), audio.samples.len());
+    }
+
+    #[tokio::test]
+    async fn wav_reports_pcm_codec() {
+        let audio = load_audio_bytes(test_wav_bytes(22_050, 0.5), Some("wav".to_string())).await.unwrap();
+        assert_eq!(audio.codec.codec.as_deref(), Some("pcm_s16le"));
+        assert_eq!(audio.codec.bits_per_sample, Some(16));
+        assert_eq!(audio.codec.lossless, Some(true));
+    }
+
+    #[tokio::test]
+    async fn damaged_packets_are_skipped_up_to_threshold() {
+        // 3 s at 22.05 kHz is 17 frames of 4096; Claxon rejects the damaged
+        // stream outright, Symphonia skips the bad frames
+        let flac = |damaged: &[usize]| {
+            test_flac_bytes_with(22_050, 3.0, &FlacOptions { reserved_subframes: damaged, ..Default::default() })
+        };
+
+        let audio = load_audio_bytes(flac(&[5]), Some("flac".to_string())).await.unwrap();
+        assert_eq!(audio.decode, DecodeStats { partial: true, skipped_packets: 1 });
+        assert_eq!(audio.samples.len(), 66_150 - 4096);
+
+        match load_audio_bytes(flac(&[1, 3, 5, 7, 9, 11]), Some("flac".to_string())).await {
+            Err(FingerprintError::InvalidAudio(msg)) => assert!(msg.contains("11 of 17"), "{}", msg),
+            other => panic!("expected InvalidAudio, got {:?}", other.map(|a| a.decode)),
+        }
+    }
+
+    #[tokio::test]
+    async fn aac_loads_from_adts_and_mp4() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let cases = [
+            ("adts.aac", test_adts_bytes(20)),
+            ("mp4.m4a", test_mp4_aac_bytes(20)),
+            // Mislabeled and unlabeled: the container is detected from content
+            ("adts-as.m4a", test_adts_bytes(20)),
+            ("adts-no-extension", test_adts_bytes(20)),
+        ];
+        for (name, bytes) in cases {
+            let path = dir.join(format!("fingerprint-server-{}-{}", id, name));
+            std::fs::write(&path, bytes).unwrap();
+            let audio = load_audio(path.to_str().unwrap()).await;
+            std::fs::remove_file(&path).ok();
+            let audio = audio.unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+            assert_eq!((audio.sample_rate, audio.channels), (44_100, 1), "{}", name);
+            assert_eq!(audio.samples.len(), 20 * AAC_FRAME_SAMPLES, "{}", name);
+            assert_eq!(audio.codec.codec.as_deref(), Some("aac"), "{}", name);
+            assert_eq!(audio.codec.lossless, Some(false), "{}", name);
+        }
+    }
+
+    #[test]
+    fn hints_are_registered_symphonia_extensions() {
+        assert_eq!(detect_format_from_extension("/music/a.M4B").as_deref(), Some("m4a"));
+        assert_eq!(detect_format_from_extension("/music/a.adts").as_deref(), Some("aac"));
+        assert_eq!(detect_format_from_extension("/music/no-extension"), None);
+        // Whatever the hint, it must name a reader; no reader means no hint
+        assert_eq!(format!("{:?}", probe_hint(Some("aac"))), format!("{:?}", Hint::new().with_extension("aac")));
+        assert_eq!(format!("{:?}", probe_hint(Some("alac"))), format!("{:?}", Hint::new().with_extension("m4a")));
+        assert_eq!(format!("{:?}", probe_hint(Some("wma"))), format!("{:?}", Hint::new()));
+    }
+
+    #[tokio::test]
+    async fn mislabeled_files_decode_by_content() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let cases = [
+            ("mp3-as.wav", test_mp3_bytes(40), "mp3", 44_100),
+            ("flac-as.mp3", test_flac_bytes(22_050, 1.0), "flac", 22_050),
+            ("flac-no-extension", test_flac_bytes(22_050, 1.0), "flac", 22_050),
+        ];
+        for (name, bytes, codec, sample_rate) in cases {
+            let path = dir.join(format!("fingerprint-server-{}-{}", id, name));
+            std::fs::write(&path, bytes).unwrap();
+            let audio = load_audio(path.to_str().unwrap()).await;
+            std::fs::remove_file(&path).ok();
+            let audio = audio.unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+            assert_eq!(audio.codec.codec.as_deref(), Some(codec), "{}", name);
+            assert_eq!(audio.sample_rate, sample_rate, "{}", name);
+            assert!(!audio.samples.is_empty(), "{}", name);
+        }
+
+        // Uploads: the content also overrides a wrong format header
+        let audio = load_audio_bytes(test_mp3_bytes(40), Some("flac".to_string())).await.unwrap();
+        assert_eq!(audio.codec.codec.as_deref(), Some("mp3"));
+
+        assert_eq!(sniff_format(&test_wav_bytes(8000, 0.1)), Some("wav"));
+        assert_eq!(sniff_format(&test_adts_bytes(1)), Some("aac"));
+        assert_eq!(sniff_format(&test_mp4_aac_bytes(1)), Some("m4a"));
+        assert_eq!(sniff_format(b"plain text"), None);
+    }
 }
@@ -1,3 +1,2 @@
 pub mod loader;
-
-pub use loader::{load_audio, AudioData};
+pub mod tags;
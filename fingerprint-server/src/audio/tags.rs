@@ -0,0 +1,155 @@
+//! Tag and cover-art extraction.
+//!
+//! Library-management clients want title/artist/album and whether a track
+//! has cover art alongside its fingerprint. Symphonia parses both while
+//! probing the container (ID3v2 before an MP3, Vorbis comments and PICTURE
+//! blocks in a FLAC header, the `ilst` atom in an MP4), so reading them
+//! costs one header parse and no audio decoding, whichever decoder the
+//! fingerprint itself uses.
+
+use crate::error::{FingerprintError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSourceStream, ReadOnlySource};
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey, Visual};
+
+use super::loader::{detect_format_from_extension, probe_hint};
+
+/// Common tags of a track; absent tags are `None`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub genre: Option<String>,
+    pub date: Option<String>,
+    pub track_number: Option<String>,
+    /// The front cover, or the first embedded picture if none is marked as one
+    pub cover_art: Option<CoverArt>,
+}
+
+/// An embedded picture, described without its image data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverArt {
+    /// MIME type ("image/jpeg", "image/png", ...)
+    pub media_type: String,
+    pub size_bytes: usize,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl TrackTags {
+    /// Merge one metadata revision; tags already set are kept, so the
+    /// first source read wins
+    fn merge(&mut self, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let slot = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::Album) => &mut self.album,
+                Some(StandardTagKey::AlbumArtist) => &mut self.album_artist,
+                Some(StandardTagKey::Genre) => &mut self.genre,
+                Some(StandardTagKey::Date) => &mut self.date,
+                Some(StandardTagKey::TrackNumber) => &mut self.track_number,
+                _ => continue,
+            };
+            let value = tag.value.to_string();
+            if slot.is_none() && !value.trim().is_empty() {
+                *slot = Some(value);
+            }
+        }
+
+        if self.cover_art.is_none() {
+            let visuals = revision.visuals();
+            self.cover_art = visuals
+                .iter()
+                .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+                .or_else(|| visuals.first())
+                .map(CoverArt::from_visual);
+        }
+    }
+}
+
+impl CoverArt {
+    fn from_visual(visual: &Visual) -> Self {
+        Self {
+            media_type: visual.media_type.clone(),
+            size_bytes: visual.data.len(),
+            width: visual.dimensions.map(|d| d.width),
+            height: visual.dimensions.map(|d| d.height),
+        }
+    }
+}
+
+/// Read the tags and cover-art details of the file at `filepath` (blocking)
+///
+/// Only the container headers are parsed; no audio packet is decoded.
+/// Metadata found before the container (e.g. ID3v2) takes precedence over
+/// the container's own.
+pub fn read_tags(filepath: &str) -> Result<TrackTags> {
+    let file = File::open(filepath)
+        .map_err(|e| FingerprintError::FileNotFound(format!("{}: {}", filepath, e)))?;
+    let mss = MediaSourceStream::new(Box::new(ReadOnlySource::new(file)), Default::default());
+
+    let hint = probe_hint(detect_format_from_extension(filepath).as_deref());
+
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| FingerprintError::UnsupportedFormat(format!("Failed to probe {}: {}", filepath, e)))?;
+
+    let mut tags = TrackTags::default();
+    if let Some(mut metadata) = probed.metadata.get() {
+        if let Some(revision) = metadata.skip_to_latest() {
+            tags.merge(revision);
+        }
+    }
+    if let Some(revision) = probed.format.metadata().skip_to_latest() {
+        tags.merge(revision);
+    }
+    Ok(tags)
+}
+
+/// `read_tags` on a blocking thread
+pub async fn read_tags_async(filepath: &str) -> Result<TrackTags> {
+    let filepath = filepath.to_string();
+    tokio::task::spawn_blocking(move || read_tags(&filepath))
+        .await
+        .map_err(|e| FingerprintError::InternalError(format!("Task join error: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_flac_bytes_with, FlacOptions};
+
+    #[test]
+    fn flac_vorbis_comments_and_picture_are_read() {
+        let picture = vec![0xAB; 1234];
+        let bytes = test_flac_bytes_with(
+            22_050,
+            1.0,
+            &FlacOptions {
+                comments: &[("TITLE", "Test Tone"), ("ARTIST", "Auralis"), ("ALBUM", "Fixtures"), ("TRACKNUMBER", "3")],
+                picture: Some(("image/png", 640, 480, &picture)),
+                ..Default::default()
+            },
+        );
+        let path = std::env::temp_dir().join(format!("fingerprint-server-{}-tags.flac", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let tags = read_tags(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        let tags = tags.unwrap();
+
+        assert_eq!(tags.title.as_deref(), Some("Test Tone"));
+        assert_eq!(tags.artist.as_deref(), Some("Auralis"));
+        assert_eq!(tags.album.as_deref(), Some("Fixtures"));
+        assert_eq!(tags.track_number.as_deref(), Some("3"));
+        assert_eq!(tags.genre, None);
+        assert_eq!(
+            tags.cover_art,
+            Some(CoverArt { media_type: "image/png".to_string(), size_bytes: 1234, width: Some(640), height: Some(480) })
+        );
+    }
+}
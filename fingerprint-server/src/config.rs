@@ -0,0 +1,64 @@
+//! Command-line and environment-driven server settings.
+
+use std::time::Duration;
+
+/// Read a whole number of seconds from environment variable `var`, falling
+/// back to `default` when unset or unparsable
+pub fn duration_from_env(var: &str, default: Duration) -> Duration {
+    match std::env::var(var) {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                tracing::warn!(
+                    "Ignoring invalid {}={:?}, using {}s",
+                    var,
+                    value,
+                    default.as_secs()
+                );
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+/// Read a count from environment variable `var`, falling back to `default`
+/// when unset or unparsable
+pub fn count_from_env(var: &str, default: usize) -> usize {
+    match std::env::var(var) {
+        Ok(value) => value.trim().parse::<usize>().unwrap_or_else(|_| {
+            tracing::warn!("Ignoring invalid {}={:?}, using {}", var, value, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Read a fraction in [0, 1] from environment variable `var`, falling back
+/// to `default` when unset, unparsable or out of range
+pub fn fraction_from_env(var: &str, default: f64) -> f64 {
+    match std::env::var(var) {
+        Ok(value) => match value.trim().parse::<f64>() {
+            Ok(fraction) if (0.0..=1.0).contains(&fraction) => fraction,
+            _ => {
+                tracing::warn!("Ignoring invalid {}={:?}, using {}", var, value, default);
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+/// Value of command-line option `name`, given as `name value` or `name=value`
+pub fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
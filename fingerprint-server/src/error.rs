@@ -20,9 +20,21 @@ pub enum FingerprintError {
     #[error("Invalid audio: {0}")]
     InvalidAudio(String),
 
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Analysis failed: {0}")]
     AnalysisError(String),
 
+    #[error("Server busy: {0}")]
+    Overloaded(String),
+
+    #[error("Analysis timed out: {0}")]
+    Timeout(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -37,7 +49,11 @@ impl IntoResponse for FingerprintError {
             FingerprintError::UnsupportedFormat(msg) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg),
             FingerprintError::DecodingError(msg) => (StatusCode::BAD_REQUEST, msg),
             FingerprintError::InvalidAudio(msg) => (StatusCode::BAD_REQUEST, msg),
+            FingerprintError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            FingerprintError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             FingerprintError::AnalysisError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            FingerprintError::Overloaded(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            FingerprintError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg),
             FingerprintError::IoError(err) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
             }
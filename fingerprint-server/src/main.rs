@@ -1,10 +1,15 @@
 mod api;
 mod audio;
 mod analysis;
+mod config;
 mod error;
 mod models;
+mod shutdown;
+#[cfg(test)]
+mod test_support;
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
@@ -12,6 +17,31 @@ use std::net::SocketAddr;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber;
 
+/// Build the application router (shared with the route tests)
+fn build_router() -> Router {
+    build_router_with_limiter(api::limiter::ConcurrencyLimiter::new(
+        api::limiter::max_concurrent_from_env(),
+    ))
+}
+
+/// Build the application router with the given concurrent-request limit
+fn build_router_with_limiter(limiter: api::limiter::ConcurrencyLimiter) -> Router {
+    Router::new()
+        .route("/health", get(api::health::health_handler))
+        .route("/fingerprint", post(api::fingerprint::fingerprint_handler))
+        .route("/fingerprint/ws", get(api::ws::fingerprint_ws_handler))
+        .route(
+            "/fingerprint/raw",
+            post(api::fingerprint::fingerprint_raw_handler)
+                .layer(DefaultBodyLimit::max(api::fingerprint::MAX_RAW_BODY_BYTES)),
+        )
+        .route("/analyze/stream", post(api::streaming::streaming_analysis_handler))
+        .route("/probe", post(api::probe::probe_handler))
+        .with_state(limiter)
+        .layer(CorsLayer::permissive())
+        .layer(tower_http::trace::TraceLayer::new_for_http())
+}
+
 fn main() {
     // Create Tokio runtime with aggressive thread pool configuration
     // CRITICAL: With 32 concurrent Python workers, we need both large async workers AND large blocking pool
@@ -25,6 +55,8 @@ fn main() {
         .build()
         .expect("Failed to build Tokio runtime");
 
+    let drain_timeout = shutdown::drain_timeout_from_env();
+
     rt.block_on(async {
         // Initialize logging
         tracing_subscriber::fmt()
@@ -36,13 +68,32 @@ fn main() {
 
         tracing::info!("Starting Fingerprint Server v0.1.0");
         tracing::info!("Runtime: 32 async workers + 64 blocking threads");
+        tracing::info!(
+            "Concurrent request limit: {}",
+            api::limiter::max_concurrent_from_env()
+        );
+        tracing::info!(
+            "Analysis deadline: {}s per request",
+            api::fingerprint::analysis_timeout().as_secs()
+        );
+
+        // The server opens any path a client names; --allowed-root (or
+        // FINGERPRINT_ALLOWED_ROOT) confines requests to one directory tree
+        // for shared hosts and containers, where loopback-only isn't enough.
+        let cli_root = config::arg_value("--allowed-root").map(std::path::PathBuf::from);
+        match api::validation::init_allowed_root(cli_root) {
+            Ok(Some(root)) => tracing::info!("Serving files under {}", root.display()),
+            Ok(None) => tracing::warn!(
+                "No --allowed-root or {} set: any path readable by the server is accepted",
+                api::validation::ALLOWED_ROOT_ENV
+            ),
+            Err(e) => {
+                tracing::error!("Invalid allowed root: {}", e);
+                std::process::exit(2);
+            }
+        }
 
-        // Build router
-        let app = Router::new()
-            .route("/health", get(api::health::health_handler))
-            .route("/fingerprint", post(api::fingerprint::fingerprint_handler))
-            .layer(CorsLayer::permissive())
-            .layer(tower_http::trace::TraceLayer::new_for_http());
+        let app = build_router();
 
         // Bind to socket
         let addr = SocketAddr::from(([127, 0, 0, 1], 8766));
@@ -52,9 +103,14 @@ fn main() {
 
         tracing::info!("Server listening on {}", addr);
 
-        // Run server
-        axum::serve(listener, app)
+        // Run server until SIGINT/SIGTERM, then drain in-flight requests
+        shutdown::serve_with_shutdown(listener, app, shutdown::shutdown_signal(), drain_timeout)
             .await
             .expect("Server error");
     });
+
+    // Analyses that outlived the drain timeout are still on blocking threads;
+    // don't let them hold the process open indefinitely.
+    rt.shutdown_timeout(std::time::Duration::from_secs(1));
+    tracing::info!("Server stopped");
 }
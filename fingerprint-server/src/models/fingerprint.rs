@@ -18,13 +18,13 @@ pub struct Fingerprint {
     pub low_mid_pct: f64,         // Energy in low-mids (250-500 Hz)
     pub mid_pct: f64,             // Energy in mids (500-2k Hz)
     pub upper_mid_pct: f64,       // Energy in upper-mids (2k-4k Hz)
-    pub presence_pct: f64,        // Energy in presence (4k-6k Hz)
-    pub air_pct: f64,             // Energy in air/high-freq (6k-20k Hz)
+    pub presence_pct: f64,        // Energy in presence (4k-8k Hz)
+    pub air_pct: f64,             // Energy in air/high-freq (8k-20k Hz)
 
     // Dynamics (3D)
     pub lufs: f64,                // Integrated loudness (LUFS)
     pub crest_db: f64,            // Crest factor in dB
-    pub bass_mid_ratio: f64,      // Bass to mid energy ratio
+    pub bass_mid_ratio: f64,      // Bass (60-250 Hz) vs mid (500-2000 Hz) power, dB
 
     // Temporal (4D)
     pub tempo_bpm: f64,           // Detected tempo in BPM
@@ -84,7 +84,55 @@ impl Default for Fingerprint {
     }
 }
 
+/// Spectral centroid (Hz) that maps to 1.0 (`schema.CENTROID_NORMALIZATION_HZ`)
+const CENTROID_NORMALIZATION_HZ: f64 = 8_000.0;
+
+/// Spectral rolloff (Hz) that maps to 1.0
+const ROLLOFF_NORMALIZATION_HZ: f64 = 10_000.0;
+
+/// Dynamic-range variation std (dB) that maps to 1.0
+const DRV_NORMALIZATION_DB: f64 = 6.0;
+
 impl Fingerprint {
+    /// Convert a DSP-crate fingerprint to schema keys and units
+    ///
+    /// Mirror of `rust_fingerprint_to_schema` in
+    /// `auralis/analysis/fingerprint/rust_fingerprint.py`: bands and
+    /// `loudness_variation` are renamed only, centroid/rolloff go from Hz to
+    /// 0-1 and dynamic-range variation from dB to 0-1; everything else
+    /// passes through.
+    pub fn from_dsp(fp: &auralis_dsp::AudioFingerprint) -> Self {
+        let clip01 = |x: f64| x.clamp(0.0, 1.0);
+
+        Self {
+            sub_bass_pct: fp.sub_bass as f64,
+            bass_pct: fp.bass as f64,
+            low_mid_pct: fp.low_mid as f64,
+            mid_pct: fp.mid as f64,
+            upper_mid_pct: fp.upper_mid as f64,
+            presence_pct: fp.presence as f64,
+            air_pct: fp.air as f64,
+            lufs: fp.lufs as f64,
+            crest_db: fp.crest_db as f64,
+            bass_mid_ratio: fp.bass_mid_ratio as f64,
+            tempo_bpm: fp.tempo_bpm as f64,
+            rhythm_stability: fp.rhythm_stability as f64,
+            transient_density: fp.transient_density as f64,
+            silence_ratio: fp.silence_ratio as f64,
+            spectral_centroid: clip01(fp.spectral_centroid as f64 / CENTROID_NORMALIZATION_HZ),
+            spectral_rolloff: clip01(fp.spectral_rolloff as f64 / ROLLOFF_NORMALIZATION_HZ),
+            spectral_flatness: fp.spectral_flatness as f64,
+            harmonic_ratio: fp.harmonic_ratio as f64,
+            pitch_stability: fp.pitch_stability as f64,
+            chroma_energy: fp.chroma_energy as f64,
+            dynamic_range_variation: clip01(fp.dynamic_range_variation as f64 / DRV_NORMALIZATION_DB),
+            loudness_variation_std: fp.loudness_variation as f64,
+            peak_consistency: fp.peak_consistency as f64,
+            stereo_width: fp.stereo_width as f64,
+            phase_correlation: fp.phase_correlation as f64,
+        }
+    }
+
     /// Count number of valid (non-NaN, non-infinite) dimensions
     pub fn valid_dimensions(&self) -> usize {
         [
@@ -5,6 +5,15 @@ use super::Fingerprint;
 pub struct FingerprintRequest {
     pub track_id: u32,
     pub filepath: String,
+    /// Also return the file's tags and cover-art details
+    #[serde(default)]
+    pub extract_tags: bool,
+}
+
+/// Query parameters for `POST /fingerprint/raw`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RawFingerprintParams {
+    pub track_id: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,6 +21,34 @@ pub struct FingerprintResponse {
     pub track_id: u32,
     pub fingerprint: Fingerprint,
     pub metadata: AudioMetadata,
+    /// Present when the request set `extract_tags`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<crate::audio::tags::TrackTags>,
+    pub processing_time_ms: u128,
+}
+
+/// Response of `POST /analyze/stream`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamingAnalysisResponse {
+    pub track_id: u32,
+    pub analysis: crate::analysis::StreamingAnalysis,
+    pub metadata: AudioMetadata,
+    pub processing_time_ms: u128,
+}
+
+/// Body of `POST /probe`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProbeRequest {
+    pub filepath: String,
+}
+
+/// Response of `POST /probe`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProbeResponse {
+    /// Format name from the extension
+    pub format: String,
+    #[serde(flatten)]
+    pub probe: crate::audio::loader::ProbeInfo,
     pub processing_time_ms: u128,
 }
 
@@ -20,7 +57,18 @@ pub struct AudioMetadata {
     pub duration_sec: f64,
     pub sample_rate: u32,
     pub channels: u16,
+    /// Format name from the extension (or upload hint)
     pub format: String,
+    /// Codec, bit depth and channel layout reported by the decoder
+    #[serde(flatten)]
+    pub codec: crate::audio::loader::CodecInfo,
+    /// Whether undecodable packets were skipped, and how many
+    #[serde(flatten)]
+    pub decode: crate::audio::loader::DecodeStats,
+    /// 4x oversampled peak of the decoded (mono-mixed) signal, dBTP; not
+    /// measured by `/analyze/stream`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub true_peak_dbtp: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,4 +76,29 @@ pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub uptime_sec: u64,
+    /// Requests currently being decoded or analyzed (nothing queues: at
+    /// `max_concurrent` further requests are refused)
+    pub in_flight: usize,
+    /// Concurrent-request limit; further requests get a 503
+    pub max_concurrent: usize,
+}
+
+/// Message sent to WebSocket clients of `/fingerprint/ws`
+///
+/// Serialized with a `status` tag: `decoding`, `analyzing` (with `percent`),
+/// then exactly one terminal `done` (the full `FingerprintResponse`) or `error`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProgressMessage {
+    Decoding,
+    Analyzing {
+        percent: u8,
+    },
+    Done {
+        #[serde(flatten)]
+        response: Box<FingerprintResponse>,
+    },
+    Error {
+        error: String,
+    },
 }
@@ -0,0 +1,176 @@
+//! Graceful shutdown: stop accepting connections on SIGINT/SIGTERM and give
+//! in-flight fingerprint requests a bounded time to finish.
+
+use axum::Router;
+use std::future::Future;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Default time allowed for in-flight requests after a shutdown signal
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Drain timeout from `FINGERPRINT_SHUTDOWN_TIMEOUT_SECS`, falling back to
+/// `DEFAULT_DRAIN_TIMEOUT` when unset or invalid
+pub fn drain_timeout_from_env() -> Duration {
+    crate::config::duration_from_env("FINGERPRINT_SHUTDOWN_TIMEOUT_SECS", DEFAULT_DRAIN_TIMEOUT)
+}
+
+/// Resolves on Ctrl+C or (on Unix) SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl+C"),
+        _ = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and
+/// wait up to `drain_timeout` for in-flight requests to complete
+///
+/// Requests still running after the timeout are abandoned (their blocking
+/// analysis threads are reclaimed by the runtime shutdown in `main`).
+pub async fn serve_with_shutdown<F>(
+    listener: TcpListener,
+    app: Router,
+    signal: F,
+    drain_timeout: Duration,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (signalled_tx, signalled_rx) = tokio::sync::oneshot::channel::<()>();
+    let signal = async move {
+        signal.await;
+        tracing::info!(
+            "Shutting down: no new connections, draining in-flight requests (up to {}s)",
+            drain_timeout.as_secs()
+        );
+        let _ = signalled_tx.send(());
+    };
+
+    let server = std::future::IntoFuture::into_future(
+        axum::serve(listener, app).with_graceful_shutdown(signal),
+    );
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => return result,
+        _ = signalled_rx => {}
+    }
+
+    match tokio::time::timeout(drain_timeout, server).await {
+        Ok(result) => {
+            tracing::info!("All in-flight requests drained");
+            result
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Drain timeout of {}s elapsed with requests still in flight",
+                drain_timeout.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_wav_bytes;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Minimal HTTP/1.1 POST of a WAV body to /fingerprint/raw, returning the
+    /// raw response text
+    async fn post_raw_wav(addr: std::net::SocketAddr, body: Vec<u8>) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let head = format!(
+            "POST /fingerprint/raw?track_id=3 HTTP/1.1\r\nHost: localhost\r\n\
+             X-Audio-Format: wav\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.write_all(&body).await.unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn in_flight_request_completes_after_shutdown_signal() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger_tx, trigger_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            crate::build_router(),
+            async move {
+                let _ = trigger_rx.await;
+            },
+            Duration::from_secs(120),
+        ));
+
+        let request = tokio::spawn(post_raw_wav(addr, test_wav_bytes(22_050, 3.0)));
+        // Let the request reach the analyzer before shutting down
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        trigger_tx.send(()).unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            tokio::net::TcpStream::connect(addr).await.is_err(),
+            "new connections must be refused after the shutdown signal"
+        );
+
+        let response = request.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "unexpected response: {}", response);
+        assert!(response.contains("\"track_id\":3"));
+
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn drain_timeout_bounds_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/hang",
+            axum::routing::get(std::future::pending::<&'static str>),
+        );
+        let (trigger_tx, trigger_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            app,
+            async move {
+                let _ = trigger_rx.await;
+            },
+            Duration::from_millis(200),
+        ));
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /hang HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        trigger_tx.send(()).unwrap();
+
+        let finished = tokio::time::timeout(Duration::from_secs(5), server).await;
+        assert!(finished.is_ok(), "shutdown must not wait past the drain timeout");
+    }
+}
@@ -0,0 +1,328 @@
+//! Helpers shared by the route tests.
+
+use std::io::Cursor;
+use std::net::SocketAddr;
+
+/// `seconds` of a two-tone chord with a decaying click every half second,
+/// as 16-bit samples
+fn test_signal(sample_rate: u32, seconds: f64) -> impl Iterator<Item = i16> {
+    let n = (sample_rate as f64 * seconds) as usize;
+    let beat = sample_rate as usize / 2;
+    (0..n).map(move |i| {
+        let t = i as f64 / sample_rate as f64;
+        let tone = 0.3 * (2.0 * std::f64::consts::PI * 220.0 * t).sin()
+            + 0.2 * (2.0 * std::f64::consts::PI * 330.0 * t).sin();
+        let click = 0.4 * (-((i % beat) as f64) / 200.0).exp();
+        ((tone + click) * i16::MAX as f64 * 0.8) as i16
+    })
+}
+
+/// Encode the test signal as 16-bit mono WAV bytes.
+pub fn test_wav_bytes(sample_rate: u32, seconds: f64) -> Vec<u8> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).expect("wav writer");
+        for sample in test_signal(sample_rate, seconds) {
+            writer.write_sample(sample).expect("write sample");
+        }
+        writer.finalize().expect("finalize wav");
+    }
+    cursor.into_inner()
+}
+
+/// Encode the test signal as 16-bit mono FLAC bytes.
+///
+/// No encoder is available to the tests, so this writes the simplest valid
+/// stream: a STREAMINFO block, then fixed-size frames of uncompressed
+/// (verbatim) subframes with their CRCs.
+pub fn test_flac_bytes(sample_rate: u32, seconds: f64) -> Vec<u8> {
+    test_flac_bytes_with(sample_rate, seconds, &FlacOptions::default())
+}
+
+/// Extras for a test FLAC
+#[derive(Default)]
+pub struct FlacOptions<'a> {
+    /// Vorbis comments as (field, value) pairs
+    pub comments: &'a [(&'a str, &'a str)],
+    /// Front cover as (MIME type, width, height, image data)
+    pub picture: Option<(&'a str, u32, u32, &'a [u8])>,
+    /// Indices of frames whose subframe gets a reserved type: framing and
+    /// CRCs stay valid, but no decoder can decode them
+    pub reserved_subframes: &'a [usize],
+}
+
+/// `test_flac_bytes` with a VORBIS_COMMENT and/or PICTURE block after
+/// STREAMINFO, and optionally undecodable frames
+pub fn test_flac_bytes_with(sample_rate: u32, seconds: f64, options: &FlacOptions) -> Vec<u8> {
+    const BLOCK: usize = 4096;
+    let samples: Vec<i16> = test_signal(sample_rate, seconds).collect();
+
+    let mut streaminfo = Vec::with_capacity(34);
+    streaminfo.extend_from_slice(&(BLOCK as u16).to_be_bytes()); // min block size
+    streaminfo.extend_from_slice(&(BLOCK as u16).to_be_bytes()); // max block size
+    streaminfo.extend_from_slice(&[0; 6]); // min/max frame size unknown
+    // 20-bit sample rate, 3-bit channels - 1, 5-bit bits per sample - 1,
+    // 36-bit total samples
+    let packed = (sample_rate as u64) << 44 | 15u64 << 36 | samples.len() as u64;
+    streaminfo.extend_from_slice(&packed.to_be_bytes());
+    streaminfo.extend_from_slice(&[0; 16]); // MD5 unknown
+
+    let mut blocks = vec![(0u8, streaminfo)];
+    if !options.comments.is_empty() {
+        // Little-endian lengths, unlike the rest of FLAC
+        let mut body = Vec::new();
+        let vendor = b"auralis test";
+        body.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        body.extend_from_slice(vendor);
+        body.extend_from_slice(&(options.comments.len() as u32).to_le_bytes());
+        for (field, value) in options.comments {
+            let comment = format!("{}={}", field, value);
+            body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            body.extend_from_slice(comment.as_bytes());
+        }
+        blocks.push((4, body));
+    }
+    if let Some((media_type, width, height, data)) = options.picture {
+        let mut body = Vec::new();
+        body.extend_from_slice(&3u32.to_be_bytes()); // front cover
+        body.extend_from_slice(&(media_type.len() as u32).to_be_bytes());
+        body.extend_from_slice(media_type.as_bytes());
+        body.extend_from_slice(&0u32.to_be_bytes()); // empty description
+        for field in [width, height, 24, 0] {
+            // width, height, colour depth, palette size
+            body.extend_from_slice(&field.to_be_bytes());
+        }
+        body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(data);
+        blocks.push((6, body));
+    }
+
+    let mut out = b"fLaC".to_vec();
+    let last = blocks.len() - 1;
+    for (i, (kind, body)) in blocks.into_iter().enumerate() {
+        // Last-block flag, 7-bit type, 24-bit length
+        out.push(if i == last { 0x80 | kind } else { kind });
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        out.extend_from_slice(&body);
+    }
+
+    for (index, block) in samples.chunks(BLOCK).enumerate() {
+        // Sync code, fixed block size; block size from the end of the
+        // header (0x7), rate from STREAMINFO (0x0); mono, 16 bits
+        let mut frame = vec![0xFF, 0xF8, 0x70, 0x08];
+        match index {
+            0..=0x7F => frame.push(index as u8),
+            _ => frame.extend_from_slice(&[0xC0 | (index >> 6) as u8, 0x80 | (index & 0x3F) as u8]),
+        }
+        frame.extend_from_slice(&(block.len() as u16 - 1).to_be_bytes());
+        frame.push(crc8(&frame));
+
+        // Verbatim subframe (or reserved type 0b000010), no wasted bits
+        frame.push(if options.reserved_subframes.contains(&index) { 0x04 } else { 0x02 });
+        for sample in block {
+            frame.extend_from_slice(&sample.to_be_bytes());
+        }
+        frame.extend_from_slice(&crc16(&frame).to_be_bytes());
+        out.extend_from_slice(&frame);
+    }
+    out
+}
+
+/// One silent mono AAC-LC frame: a single channel element with no
+/// scale-factor bands (so no spectral data), then END
+const SILENT_AAC_FRAME: [u8; 4] = [0x00, 0x00, 0x00, 0x07];
+
+/// Samples decoded from each AAC frame
+pub const AAC_FRAME_SAMPLES: usize = 1024;
+
+/// `frames` silent AAC-LC frames at 44.1 kHz mono in ADTS framing (a
+/// `.aac` file)
+pub fn test_adts_bytes(frames: usize) -> Vec<u8> {
+    let frame_len = 7 + SILENT_AAC_FRAME.len();
+    // Sync, MPEG-4, no CRC; LC, 44.1 kHz (index 4), mono; 13-bit frame
+    // length; buffer fullness 0x7FF (VBR); one raw data block
+    let header = [
+        0xFF,
+        0xF1,
+        0x50,
+        0x40 | (frame_len >> 11) as u8,
+        (frame_len >> 3) as u8,
+        ((frame_len & 0x7) << 5) as u8 | 0x1F,
+        0xFC,
+    ];
+    let mut out = Vec::with_capacity(frames * frame_len);
+    for _ in 0..frames {
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&SILENT_AAC_FRAME);
+    }
+    out
+}
+
+/// The frames of `test_adts_bytes` in an MP4 container (a `.m4a` file):
+/// one track, one chunk, no ADTS headers
+pub fn test_mp4_aac_bytes(frames: usize) -> Vec<u8> {
+    const RATE: u32 = 44_100;
+    let duration = (frames * AAC_FRAME_SAMPLES) as u32;
+    let be = |v: u32| v.to_be_bytes();
+
+    let ftyp = mp4_atom(b"ftyp", &[b"M4A ".as_slice(), &be(0), b"M4A ", b"isom"].concat());
+
+    let mvhd = [&be(0)[..], &be(0), &be(0), &be(RATE), &be(duration), &be(0x0001_0000), &[1, 0], &[0; 10], &MP4_MATRIX, &[0; 24], &be(2)].concat();
+    let tkhd = [&be(7)[..], &be(0), &be(0), &be(1), &be(0), &be(duration), &[0; 8], &[0, 0, 0, 0, 1, 0, 0, 0], &MP4_MATRIX, &be(0), &be(0)].concat();
+    let mdhd = [&be(0)[..], &be(0), &be(0), &be(RATE), &be(duration), &[0x55, 0xC4, 0, 0]].concat();
+    let hdlr = [&be(0)[..], &be(0), b"soun", &[0; 12], b"SoundHandler\0"].concat();
+
+    // ES descriptor: decoder config (AAC, audio stream) carrying the
+    // AudioSpecificConfig (LC, 44.1 kHz, mono), then a predefined SL config
+    let esds = [
+        &be(0)[..],
+        &[0x03, 25, 0, 1, 0],
+        &[0x04, 17, 0x40, 0x15, 0, 0, 0],
+        &be(0),
+        &be(0),
+        &[0x05, 2, 0x12, 0x08],
+        &[0x06, 1, 0x02],
+    ]
+    .concat();
+    let mp4a = [&[0; 6][..], &[0, 1], &[0; 8], &[0, 1, 0, 16, 0, 0, 0, 0], &be(RATE << 16), &mp4_atom(b"esds", &esds)].concat();
+    let stsd = [&be(0)[..], &be(1), &mp4_atom(b"mp4a", &mp4a)].concat();
+    let stts = [be(0), be(1), be(frames as u32), be(AAC_FRAME_SAMPLES as u32)].concat();
+    let stsc = [be(0), be(1), be(1), be(frames as u32), be(1)].concat();
+    let stsz = [be(0), be(SILENT_AAC_FRAME.len() as u32), be(frames as u32)].concat();
+
+    // The chunk offset depends on the size of moov, which doesn't depend on
+    // the offset's value
+    let moov = |chunk_offset: u32| {
+        let stco = [be(0), be(1), be(chunk_offset)].concat();
+        let stbl = [
+            mp4_atom(b"stsd", &stsd),
+            mp4_atom(b"stts", &stts),
+            mp4_atom(b"stsc", &stsc),
+            mp4_atom(b"stsz", &stsz),
+            mp4_atom(b"stco", &stco),
+        ]
+        .concat();
+        let minf = [mp4_atom(b"smhd", &[0; 8]), mp4_atom(b"stbl", &stbl)].concat();
+        let mdia = [mp4_atom(b"mdhd", &mdhd), mp4_atom(b"hdlr", &hdlr), mp4_atom(b"minf", &minf)].concat();
+        let trak = [mp4_atom(b"tkhd", &tkhd), mp4_atom(b"mdia", &mdia)].concat();
+        mp4_atom(b"moov", &[mp4_atom(b"mvhd", &mvhd), mp4_atom(b"trak", &trak)].concat())
+    };
+    let chunk_offset = (ftyp.len() + moov(0).len() + 8) as u32;
+    let mdat = mp4_atom(b"mdat", &SILENT_AAC_FRAME.repeat(frames));
+    [ftyp, moov(chunk_offset), mdat].concat()
+}
+
+/// Identity transformation matrix of the mvhd and tkhd atoms
+const MP4_MATRIX: [u8; 36] = [
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0x40, 0, 0, 0,
+];
+
+/// An MP4 atom: 32-bit size, type, body
+fn mp4_atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    [&((body.len() + 8) as u32).to_be_bytes()[..], kind, body].concat()
+}
+
+/// `frames` silent MPEG-1 Layer III frames, 128 kbps 44.1 kHz mono (a
+/// `.mp3` file)
+///
+/// Each frame is a header, zeroed side information (no main data, so
+/// every granule decodes to silence) and zero padding to the frame size.
+pub fn test_mp3_bytes(frames: usize) -> Vec<u8> {
+    // 144 * bitrate / sample rate, no padding slot
+    const FRAME_LEN: usize = 144 * 128_000 / 44_100;
+    let mut frame = vec![0u8; FRAME_LEN];
+    // Sync, MPEG-1, Layer III, no CRC; 128 kbps, 44.1 kHz; mono, original
+    frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0xC4]);
+    frame.repeat(frames)
+}
+
+/// FLAC frame header CRC (polynomial 0x07)
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 })
+    })
+}
+
+/// FLAC frame CRC (polynomial 0x8005)
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 }
+        })
+    })
+}
+
+/// Write test WAV audio to a unique file in the temp directory.
+pub fn write_test_wav(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "fingerprint-server-{}-{}.wav",
+        std::process::id(),
+        name
+    ));
+    std::fs::write(&path, test_wav_bytes(22_050, 3.0)).expect("write test wav");
+    path
+}
+
+/// Write a 16-bit PCM WAV of `seconds` of silence to a unique file in the
+/// temp directory
+///
+/// Only the header is written; the data chunk is a hole, so even hours of
+/// audio take no disk space or time to create.
+pub fn write_sparse_wav(name: &str, sample_rate: u32, channels: u16, seconds: f64) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "fingerprint-server-{}-{}.wav",
+        std::process::id(),
+        name
+    ));
+    let block_align = channels as u32 * 2;
+    let data_len = (sample_rate as f64 * seconds) as u32 * block_align;
+
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * block_align).to_le_bytes()); // byte rate
+    header.extend_from_slice(&(block_align as u16).to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+
+    let file = std::fs::File::create(&path).expect("create sparse wav");
+    std::io::Write::write_all(&mut &file, &header).expect("write wav header");
+    file.set_len(44 + data_len as u64).expect("extend sparse wav");
+    path
+}
+
+/// Serve the application router on an ephemeral local port.
+pub async fn spawn_server() -> SocketAddr {
+    spawn_server_with(crate::build_router()).await
+}
+
+/// Serve the router with the given limiter on an ephemeral local port, so a
+/// test can watch its slots
+pub async fn spawn_server_with_limiter(limiter: crate::api::limiter::ConcurrencyLimiter) -> SocketAddr {
+    spawn_server_with(crate::build_router_with_limiter(limiter)).await
+}
+
+async fn spawn_server_with(router: axum::Router) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.expect("test server");
+    });
+    addr
+}
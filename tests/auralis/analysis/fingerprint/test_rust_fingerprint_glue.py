@@ -3,7 +3,7 @@ Tests for the Rust->schema fingerprint glue (auralis.analysis.fingerprint.rust_f
 
 Verifies that compute_fingerprint_schema() produces schema-conformant 25D
 fingerprints from the in-process Rust engine: correct keys, ranges, band-sum,
-and the centroid/rolloff/DRV/bass_mid_ratio transforms.
+and the centroid/rolloff/DRV transforms.
 """
 import math
 
@@ -52,13 +52,10 @@ def test_normalized_dims_in_unit_range():
         assert 0.0 <= fp[k] <= 1.0, f"{k}={fp[k]} out of 0-1"
 
 
-def test_bass_mid_ratio_is_db_scaled():
-    # A raw fraction of 0.5 (bass==mid) must map to 0 dB.
-    raw = _make_raw(bass_mid_ratio=0.5)
-    assert abs(rust_fingerprint_to_schema(raw)["bass_mid_ratio"]) < 1e-6
-    # Bass-dominant (>0.5) -> positive dB; mid-dominant (<0.5) -> negative dB.
-    assert rust_fingerprint_to_schema(_make_raw(bass_mid_ratio=0.9))["bass_mid_ratio"] > 0
-    assert rust_fingerprint_to_schema(_make_raw(bass_mid_ratio=0.1))["bass_mid_ratio"] < 0
+def test_bass_mid_ratio_passes_through_as_db():
+    # Rust already reports 10*log10(bass/mid) power in dB, like the schema.
+    for db in (-12.0, 0.0, 18.5):
+        assert rust_fingerprint_to_schema(_make_raw(bass_mid_ratio=db))["bass_mid_ratio"] == pytest.approx(db)
 
 
 def test_centroid_rolloff_normalization():
@@ -88,7 +85,7 @@ def _make_raw(**overrides):
     raw = {
         "sub_bass": 0.1, "bass": 0.3, "low_mid": 0.2, "mid": 0.2,
         "upper_mid": 0.1, "presence": 0.05, "air": 0.05,
-        "lufs": -14.0, "crest_db": 12.0, "bass_mid_ratio": 0.5,
+        "lufs": -14.0, "crest_db": 12.0, "bass_mid_ratio": 6.0,
         "tempo_bpm": 120.0, "rhythm_stability": 0.8, "transient_density": 0.2,
         "silence_ratio": 0.05, "spectral_centroid": 2000.0,
         "spectral_rolloff": 4000.0, "spectral_flatness": 0.1,